@@ -1,6 +1,12 @@
 extern crate cc;
 
+use std::env;
+
 fn main() {
+    if env::var_os("CARGO_FEATURE_PURE_RUST").is_some() {
+        return;
+    }
+
     cc::Build::new()
         .flag("-std=c++11")
         .cpp(true)
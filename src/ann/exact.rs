@@ -0,0 +1,81 @@
+use wordvector_base::WordVectorModel;
+
+use matrix::Matrix;
+use FastText;
+
+use super::{normalize, normalized_vocabulary, Error};
+
+/// A brute-force search over the vocabulary of a loaded model, as `nn` and `analogies` of fastText do.
+pub struct ExactIndex<'a> {
+    model: &'a FastText,
+    words: Vec<String>,
+    vectors: Matrix,
+}
+
+impl<'a> ExactIndex<'a> {
+    pub fn new(model: &'a FastText) -> Result<ExactIndex<'a>, Error> {
+        let (words, vectors) = normalized_vocabulary(model)?;
+
+        Ok(ExactIndex { model, words, vectors })
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// The `k` words closest to `vector` with their cosine similarities, most similar first.
+    pub fn nearest_to_vector(&self, vector: &[f32], k: usize) -> Vec<(&str, f32)> {
+        self.nearest(vector, k, &[])
+    }
+
+    /// The `k` words closest to `word`, excluding itself.
+    pub fn nearest_to_word(&self, word: &str, k: usize) -> Vec<(&str, f32)> {
+        match self.model.word_to_vector(word) {
+            Some(vector) => self.nearest(&vector, k, &[word]),
+            None => vec![],
+        }
+    }
+
+    /// The `k` words closest to `a - b + c` of the normalized vectors, excluding the three of them.
+    pub fn analogies(&self, a: &str, b: &str, c: &str, k: usize) -> Vec<(&str, f32)> {
+        let (a_vec, b_vec, c_vec) = match (self.model.word_to_vector(a), self.model.word_to_vector(b), self.model.word_to_vector(c)) {
+            (Some(a), Some(b), Some(c)) => (a, b, c),
+            _ => return vec![],
+        };
+
+        // as fastText, the sum of the vectors scaled to unit norm
+        let mut query = vec![0f32; a_vec.len()];
+        for &(ref vector, sign) in &[(a_vec, 1f32), (b_vec, -1.0), (c_vec, 1.0)] {
+            let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+            for (q, v) in query.iter_mut().zip(vector) {
+                *q += sign * v / (norm + 1e-8);
+            }
+        }
+
+        self.nearest(&query, k, &[a, b, c])
+    }
+
+    fn nearest(&self, vector: &[f32], k: usize, exclude: &[&str]) -> Vec<(&str, f32)> {
+        if vector.len() != self.vectors.cols() {
+            return vec![];
+        }
+
+        let mut query = vector.to_owned();
+        normalize(&mut query);
+
+        let mut scored: Vec<(&str, f32)> = self.words.iter()
+            .zip(self.vectors.iter_rows())
+            .filter(|&(word, _)| !exclude.contains(&word.as_str()))
+            .map(|(word, row)| (word.as_str(), row.iter().zip(&query).map(|(r, q)| r * q).sum::<f32>()))
+            .collect();
+
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(::std::cmp::Ordering::Equal));
+        scored.truncate(k);
+
+        scored
+    }
+}
@@ -0,0 +1,386 @@
+//! Hierarchical navigable small world graphs (Malkov and Yashunin, 2016) over unit vectors.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::io::{self, Read, Write};
+
+use format::binary::{read_i32, read_i64, read_u8};
+use matrix::Matrix;
+
+use super::{Error, ModelStamp};
+
+const MAGIC: i32 = 0x48_4e_53_57;
+const VERSION: i32 = 2;
+const NO_ENTRY: i32 = -1;
+
+/// Parameters of the graph construction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HnswParams {
+    /// The number of neighbors of a node on the upper layers, twice as many on the bottom one.
+    pub m: usize,
+    /// The size of the candidate list while inserting a node.
+    pub ef_construction: usize,
+    /// The seed of the random levels of the nodes.
+    pub seed: u64,
+}
+
+impl Default for HnswParams {
+    fn default() -> HnswParams {
+        HnswParams {
+            m: 16,
+            ef_construction: 200,
+            seed: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    id: u32,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        self.distance.partial_cmp(&other.distance)
+            .unwrap_or(Ordering::Equal)
+            .then(self.id.cmp(&other.id))
+    }
+}
+
+/// The distance of unit vectors, one minus their cosine similarity.
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    1.0 - a.iter().zip(b).map(|(a, b)| a * b).sum::<f32>()
+}
+
+/// A xorshift generator, enough for drawing levels.
+struct Rng(u64);
+
+impl Rng {
+    fn uniform(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        ((self.0 >> 11) as f64 + 1.0) / (1u64 << 53) as f64
+    }
+}
+
+/// The graph of an index: the neighbors of each node on each of its layers.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Hnsw {
+    m: usize,
+    ef_construction: usize,
+    entry: Option<u32>,
+    max_level: usize,
+    links: Vec<Vec<Vec<u32>>>,
+}
+
+impl Hnsw {
+    /// Builds the graph of the rows of `vectors`, which must be unit vectors.
+    pub(crate) fn build(vectors: &Matrix, params: HnswParams) -> Hnsw {
+        let mut hnsw = Hnsw {
+            m: params.m.max(2),
+            ef_construction: params.ef_construction.max(1),
+            entry: None,
+            max_level: 0,
+            links: Vec::with_capacity(vectors.rows()),
+        };
+
+        let ml = 1.0 / (hnsw.m as f64).ln();
+        let mut rng = Rng(params.seed.max(1));
+
+        for id in 0..vectors.rows() {
+            let level = (-rng.uniform().ln() * ml) as usize;
+            hnsw.insert(vectors, id as u32, level);
+        }
+
+        hnsw
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    fn max_links(&self, level: usize) -> usize {
+        if level == 0 {
+            2 * self.m
+        } else {
+            self.m
+        }
+    }
+
+    fn insert(&mut self, vectors: &Matrix, id: u32, level: usize) {
+        self.links.push(vec![vec![]; level + 1]);
+
+        let mut entry = match self.entry {
+            Some(entry) => entry,
+            None => {
+                self.entry = Some(id);
+                self.max_level = level;
+                return;
+            }
+        };
+
+        let query = vectors.row(id as usize);
+        for lc in (level + 1..self.max_level + 1).rev() {
+            entry = self.search_layer(vectors, query, &[entry], 1, lc)[0].id;
+        }
+
+        let mut entries = vec![entry];
+        for lc in (0..level.min(self.max_level) + 1).rev() {
+            let found = self.search_layer(vectors, query, &entries, self.ef_construction, lc);
+            let neighbors: Vec<u32> = found.iter().take(self.m).map(|c| c.id).collect();
+
+            for &neighbor in &neighbors {
+                self.links[neighbor as usize][lc].push(id);
+                self.shrink(vectors, neighbor, lc);
+            }
+
+            self.links[id as usize][lc] = neighbors;
+            entries = found.into_iter().map(|c| c.id).collect();
+        }
+
+        if level > self.max_level {
+            self.entry = Some(id);
+            self.max_level = level;
+        }
+    }
+
+    /// Keeps the closest neighbors of `id` on the layer once it has too many.
+    fn shrink(&mut self, vectors: &Matrix, id: u32, level: usize) {
+        let max_links = self.max_links(level);
+        if self.links[id as usize][level].len() <= max_links {
+            return;
+        }
+
+        let vector = vectors.row(id as usize);
+        let mut candidates: Vec<Candidate> = self.links[id as usize][level].iter()
+            .map(|&n| Candidate { distance: distance(vector, vectors.row(n as usize)), id: n })
+            .collect();
+        candidates.sort();
+
+        self.links[id as usize][level] = candidates.into_iter().take(max_links).map(|c| c.id).collect();
+    }
+
+    /// The `ef` nodes of the layer closest to `query` found from `entries`, closest first.
+    fn search_layer(&self, vectors: &Matrix, query: &[f32], entries: &[u32], ef: usize, level: usize) -> Vec<Candidate> {
+        let mut visited: HashSet<u32> = entries.iter().cloned().collect();
+        let mut candidates = BinaryHeap::new();
+        let mut found = BinaryHeap::new();
+
+        for &id in entries {
+            let candidate = Candidate { distance: distance(query, vectors.row(id as usize)), id };
+            candidates.push(::std::cmp::Reverse(candidate));
+            found.push(candidate);
+        }
+        while found.len() > ef {
+            found.pop();
+        }
+
+        while let Some(::std::cmp::Reverse(candidate)) = candidates.pop() {
+            let furthest = found.peek().map(|c: &Candidate| c.distance).unwrap_or(::std::f32::INFINITY);
+            if candidate.distance > furthest && found.len() >= ef {
+                break;
+            }
+
+            for &neighbor in &self.links[candidate.id as usize][level] {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+
+                let neighbor = Candidate { distance: distance(query, vectors.row(neighbor as usize)), id: neighbor };
+                let furthest = found.peek().map(|c| c.distance).unwrap_or(::std::f32::INFINITY);
+                if found.len() < ef || neighbor.distance < furthest {
+                    candidates.push(::std::cmp::Reverse(neighbor));
+                    found.push(neighbor);
+                    if found.len() > ef {
+                        found.pop();
+                    }
+                }
+            }
+        }
+
+        found.into_sorted_vec()
+    }
+
+    /// The `k` nodes closest to the unit vector `query` with their distances, closest first.
+    pub(crate) fn search(&self, vectors: &Matrix, query: &[f32], k: usize, ef: usize) -> Vec<(u32, f32)> {
+        let mut entry = match self.entry {
+            Some(entry) => entry,
+            None => return vec![],
+        };
+
+        for lc in (1..self.max_level + 1).rev() {
+            entry = self.search_layer(vectors, query, &[entry], 1, lc)[0].id;
+        }
+
+        self.search_layer(vectors, query, &[entry], ef.max(k), 0).into_iter()
+            .take(k)
+            .map(|c| (c.id, c.distance))
+            .collect()
+    }
+
+    /// Writes the graph after the stamp of the model it was built for.
+    pub(crate) fn save<W: Write>(&self, writer: &mut W, stamp: &ModelStamp) -> io::Result<()> {
+        for value in &[MAGIC, VERSION, stamp.dim as i32] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        writer.write_all(&stamp.fingerprint.to_le_bytes())?;
+
+        for value in &[self.m as i32, self.ef_construction as i32, self.len() as i32] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        writer.write_all(&self.entry.map(|entry| entry as i32).unwrap_or(NO_ENTRY).to_le_bytes())?;
+
+        for node in &self.links {
+            writer.write_all(&[(node.len() - 1) as u8])?;
+            for neighbors in node {
+                writer.write_all(&(neighbors.len() as i32).to_le_bytes())?;
+                for &neighbor in neighbors {
+                    writer.write_all(&neighbor.to_le_bytes())?;
+                }
+            }
+        }
+
+        writer.flush()
+    }
+
+    pub(crate) fn load<R: Read>(reader: &mut R) -> Result<(Hnsw, ModelStamp), Error> {
+        let magic = read_i32(reader)?;
+        if magic != MAGIC {
+            return Err(Error::Corrupted(String::from("wrong index file magic")));
+        }
+        let version = read_i32(reader)?;
+        if version != VERSION {
+            return Err(Error::Corrupted(format!("unsupported index version {}", version)));
+        }
+
+        let stamp = ModelStamp {
+            dim: read_usize(reader, "dimension")?,
+            fingerprint: read_i64(reader)? as u64,
+        };
+
+        let m = read_usize(reader, "m")?;
+        let ef_construction = read_usize(reader, "ef_construction")?;
+        let len = read_usize(reader, "number of nodes")?;
+        let entry = match read_i32(reader)? {
+            NO_ENTRY => None,
+            entry if (entry as usize) < len => Some(entry as u32),
+            entry => return Err(Error::Corrupted(format!("wrong entry point {}", entry))),
+        };
+
+        let mut links = Vec::with_capacity(len.min(1 << 20));
+        let mut max_level = 0;
+        for _ in 0..len {
+            let level = read_u8(reader)? as usize;
+            max_level = max_level.max(level);
+
+            let mut node = Vec::with_capacity(level + 1);
+            for _ in 0..level + 1 {
+                let count = read_usize(reader, "number of neighbors")?;
+                let mut neighbors = Vec::with_capacity(count.min(1 << 10));
+                for _ in 0..count {
+                    let neighbor = read_i32(reader)? as u32;
+                    if neighbor as usize >= len {
+                        return Err(Error::Corrupted(format!("wrong neighbor {}", neighbor)));
+                    }
+                    neighbors.push(neighbor);
+                }
+                node.push(neighbors);
+            }
+            links.push(node);
+        }
+
+        // searches follow the links of a layer from the neighbors found on it
+        for node in &links {
+            for (level, neighbors) in node.iter().enumerate() {
+                if let Some(&neighbor) = neighbors.iter().find(|&&neighbor| links[neighbor as usize].len() <= level) {
+                    return Err(Error::Corrupted(format!("neighbor {} is not on layer {}", neighbor, level)));
+                }
+            }
+        }
+
+        if let Some(entry) = entry {
+            if links[entry as usize].len() - 1 != max_level {
+                return Err(Error::Corrupted(String::from("entry point is not on the top layer")));
+            }
+        }
+
+        Ok((Hnsw { m, ef_construction, entry, max_level, links }, stamp))
+    }
+}
+
+fn read_usize<R: Read>(reader: &mut R, what: &str) -> Result<usize, Error> {
+    match read_i32(reader)? {
+        value if value >= 0 => Ok(value as usize),
+        value => Err(Error::Corrupted(format!("negative {} {}", what, value))),
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    fn circle(n: usize) -> Matrix {
+        let data = (0..n)
+            .flat_map(|i| {
+                let angle = i as f32 * 2.0 * ::std::f32::consts::PI / n as f32;
+                vec![angle.cos(), angle.sin()]
+            })
+            .collect();
+
+        Matrix::new(n, 2, data)
+    }
+
+    #[test]
+    fn test_hnsw_search() {
+        let vectors = circle(500);
+        let hnsw = Hnsw::build(&vectors, HnswParams { m: 4, ef_construction: 32, seed: 7 });
+
+        for &i in &[0usize, 123, 499] {
+            let found: Vec<u32> = hnsw.search(&vectors, vectors.row(i), 3, 32).into_iter().map(|(id, _)| id).collect();
+            let expected = vec![i as u32, ((i + 1) % 500) as u32, ((i + 499) % 500) as u32];
+
+            assert_eq!(found[0], expected[0], "check nearest of {}", i);
+            assert!(found[1..].iter().all(|id| expected[1..].contains(id)), "check neighbors of {}: {:?}", i, found);
+        }
+    }
+
+    #[test]
+    fn test_hnsw_save_load() {
+        let vectors = circle(100);
+        let hnsw = Hnsw::build(&vectors, HnswParams::default());
+
+        let stamp = ModelStamp { dim: 2, fingerprint: 42 };
+
+        let mut buf = Vec::new();
+        hnsw.save(&mut buf, &stamp).unwrap();
+        assert_eq!(Hnsw::load(&mut buf.as_slice()).unwrap(), (hnsw, stamp));
+
+        buf[0] = 0;
+        assert!(Hnsw::load(&mut buf.as_slice()).is_err());
+        assert_eq!(Hnsw::build(&Matrix::zeros(0, 2), HnswParams::default()).search(&vectors, vectors.row(0), 1, 1), vec![]);
+    }
+
+    #[test]
+    fn test_hnsw_load_corrupted() {
+        // node 0 links to node 1 on layer 1, which node 1 isn't on
+        let hnsw = Hnsw { m: 4, ef_construction: 8, entry: Some(0), max_level: 1, links: vec![vec![vec![1], vec![1]], vec![vec![0]]] };
+
+        let mut buf = Vec::new();
+        hnsw.save(&mut buf, &ModelStamp { dim: 2, fingerprint: 42 }).unwrap();
+        match Hnsw::load(&mut buf.as_slice()) {
+            Err(Error::Corrupted(_)) => assert!(true),
+            res => assert!(false, "failed to raise a corrupted index error, got {:?}", res),
+        }
+    }
+}
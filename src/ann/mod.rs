@@ -0,0 +1,229 @@
+//! Nearest neighbors among the words of a model.
+//!
+//! `ExactIndex` scans the whole vocabulary. `VocabularyIndex` is approximate:
+//! an HNSW graph over the normalized word vectors. Only the graph is persisted,
+//! to a sidecar `.hnsw` file next to the `.bin`; the vectors are looked up again when it is opened.
+
+mod exact;
+mod hnsw;
+
+use std::error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use wordvector_base::WordVectorModel;
+
+use matrix::Matrix;
+use {Err, FastText};
+
+use self::hnsw::Hnsw;
+
+pub use self::exact::ExactIndex;
+pub use self::hnsw::HnswParams;
+
+/// The default size of the candidate list of queries.
+pub const DEFAULT_EF: usize = 64;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Corrupted(String),
+    /// The index was built for a model with another vocabulary size.
+    WrongModel { expected: usize, got: usize },
+    /// The index was built for a model of another dimension.
+    WrongDimension { expected: usize, got: usize },
+    /// The index was built for a model with other words or vectors.
+    WrongFingerprint,
+    /// The vectors of the vocabulary couldn't be looked up in the model.
+    Model(Err),
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<Err> for Error {
+    fn from(err: Err) -> Error {
+        Error::Model(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref err) => write!(f, "{}", err),
+            Error::Corrupted(ref msg) => write!(f, "corrupted index: {}", msg),
+            Error::WrongModel { expected, got } => write!(f, "index of {} words for a model of {} words", got, expected),
+            Error::WrongDimension { expected, got } => write!(f, "index of dimension {} for a model of dimension {}", got, expected),
+            Error::WrongFingerprint => write!(f, "index of a model with other words or vectors"),
+            Error::Model(ref err) => write!(f, "failed to look up the vocabulary vectors: {:?}", err),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// What a saved index records of the model it was built for, checked when it is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ModelStamp {
+    pub dim: usize,
+    /// A FNV-1a hash of the words and of their normalized vectors.
+    pub fingerprint: u64,
+}
+
+impl ModelStamp {
+    fn new(words: &[String], vectors: &Matrix) -> ModelStamp {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        let mut feed = |bytes: &[u8]| for &byte in bytes {
+            hash = (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        };
+
+        for (i, word) in words.iter().enumerate() {
+            feed(word.as_bytes());
+            feed(&[0]);
+            for v in vectors.row(i) {
+                feed(&v.to_bits().to_le_bytes());
+            }
+        }
+
+        ModelStamp { dim: vectors.cols(), fingerprint: hash }
+    }
+}
+
+/// The sidecar index file of a `.bin` model: `model.bin` gives `model.hnsw`.
+pub fn sidecar_path(model_path: &Path) -> PathBuf {
+    model_path.with_extension("hnsw")
+}
+
+/// An HNSW index over the vocabulary of a loaded model.
+pub struct VocabularyIndex<'a> {
+    model: &'a FastText,
+    words: Vec<String>,
+    vectors: Matrix,
+    hnsw: Hnsw,
+    ef: usize,
+}
+
+impl<'a> VocabularyIndex<'a> {
+    pub fn build(model: &'a FastText, params: HnswParams) -> Result<VocabularyIndex<'a>, Error> {
+        let (words, vectors) = normalized_vocabulary(model)?;
+        let hnsw = Hnsw::build(&vectors, params);
+
+        Ok(VocabularyIndex { model, words, vectors, hnsw, ef: DEFAULT_EF })
+    }
+
+    /// Opens an index saved by `save` for the same model, i.e. with the same words and vectors.
+    pub fn open(model: &'a FastText, path: &Path) -> Result<VocabularyIndex<'a>, Error> {
+        let (hnsw, stamp) = Hnsw::load(&mut BufReader::new(File::open(path)?))?;
+        let (words, vectors) = normalized_vocabulary(model)?;
+
+        if hnsw.len() != words.len() {
+            return Err(Error::WrongModel { expected: words.len(), got: hnsw.len() });
+        }
+        if stamp.dim != vectors.cols() {
+            return Err(Error::WrongDimension { expected: vectors.cols(), got: stamp.dim });
+        }
+        if stamp != ModelStamp::new(&words, &vectors) {
+            return Err(Error::WrongFingerprint);
+        }
+
+        Ok(VocabularyIndex { model, words, vectors, hnsw, ef: DEFAULT_EF })
+    }
+
+    /// Opens the sidecar index of the model at `model_path`, building and saving it if it is missing.
+    pub fn open_or_build(model: &'a FastText, model_path: &Path, params: HnswParams) -> Result<VocabularyIndex<'a>, Error> {
+        let path = sidecar_path(model_path);
+        if path.exists() {
+            return VocabularyIndex::open(model, &path);
+        }
+
+        let index = VocabularyIndex::build(model, params)?;
+        index.save(&path)?;
+
+        Ok(index)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let stamp = ModelStamp::new(&self.words, &self.vectors);
+
+        Ok(self.hnsw.save(&mut BufWriter::new(File::create(path)?), &stamp)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Sets the size of the candidate list of queries, trading speed for recall.
+    pub fn set_ef(&mut self, ef: usize) {
+        self.ef = ef.max(1);
+    }
+
+    /// The `k` words closest to `vector` with their cosine similarities, most similar first.
+    pub fn nearest_to_vector(&self, vector: &[f32], k: usize) -> Vec<(&str, f32)> {
+        self.nearest(vector, k, None)
+    }
+
+    /// The `k` words closest to `word`, excluding itself, as `nn` of fastText does.
+    pub fn nearest_to_word(&self, word: &str, k: usize) -> Vec<(&str, f32)> {
+        match self.model.word_to_vector(word) {
+            Some(vector) => self.nearest(&vector, k, Some(word)),
+            None => vec![],
+        }
+    }
+
+    /// The `k` words closest to the sentence vector of `text`.
+    pub fn nearest_to_sentence(&self, text: &str, k: usize) -> Vec<(&str, f32)> {
+        match self.model.sentence_vector(text) {
+            Some(vector) => self.nearest(&vector, k, None),
+            None => vec![],
+        }
+    }
+
+    fn nearest(&self, vector: &[f32], k: usize, exclude: Option<&str>) -> Vec<(&str, f32)> {
+        if vector.len() != self.vectors.cols() {
+            return vec![];
+        }
+
+        let mut query = vector.to_owned();
+        normalize(&mut query);
+
+        let excluded = exclude.is_some() as usize;
+        self.hnsw.search(&self.vectors, &query, k + excluded, self.ef.max(k + excluded)).into_iter()
+            .map(|(id, distance)| (self.words[id as usize].as_str(), 1.0 - distance))
+            .filter(|&(word, _)| Some(word) != exclude)
+            .take(k)
+            .collect()
+    }
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+
+    if norm > 0.0 {
+        for v in vector.iter_mut() {
+            *v /= norm;
+        }
+    }
+}
+
+fn normalized_vocabulary(model: &FastText) -> Result<(Vec<String>, Matrix), Err> {
+    let words = model.vocabulary();
+    let mut vectors = {
+        let words: Vec<&str> = words.iter().map(|word| word.as_str()).collect();
+        model.embed_words(&words)?
+    };
+
+    for i in 0..vectors.rows() {
+        normalize(vectors.row_mut(i));
+    }
+
+    Ok((words, vectors))
+}
@@ -0,0 +1,640 @@
+//! A command-line tool mirroring the `fasttext` binary of the C++ library.
+
+extern crate fasttext;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::process;
+
+use fasttext::ann::ExactIndex;
+use fasttext::classifier::Prediction;
+use fasttext::format::dump::format_float;
+use fasttext::format::{self, ModelKind};
+use fasttext::{Err, FastText};
+
+const USAGE: &'static str = "usage: fasttext-rs <command> <args>
+
+The commands supported by fasttext-rs are:
+
+  supervised              train a supervised classifier
+  quantize                quantize a model to reduce the memory usage
+  test                    evaluate a supervised classifier
+  test-label              print labels with precision and recall scores
+  predict                 predict most likely labels
+  predict-prob            predict most likely labels with probabilities
+  skipgram                train a skipgram model
+  cbow                    train a cbow model
+  print-word-vectors      print word vectors given a trained model
+  print-sentence-vectors  print sentence vectors given a trained model
+  print-ngrams            print ngrams given a trained model and word
+  nn                      query for nearest neighbors
+  analogies               query for analogies
+  dump                    dump arguments, dictionary, input/output vectors
+";
+
+type CliResult = Result<(), String>;
+
+fn describe(err: Err) -> String {
+    String::from(match err {
+        Err::ResErrorNotOpen => "failed to open the file",
+        Err::RerErrorWrongModel => "wrong model",
+        Err::ResErrorModelNotInit => "the model is not loaded",
+        Err::ResErrorExecution => "the command failed",
+        Err::ResErrorWrongDimension => "wrong dimension",
+    })
+}
+
+fn io_err(err: io::Error) -> String {
+    err.to_string()
+}
+
+fn usage_of(command: &str, args: &str) -> String {
+    format!("usage: fasttext-rs {} {}", command, args)
+}
+
+fn write_vector<W: Write>(out: &mut W, name: &str, vector: &[f32]) -> io::Result<()> {
+    write!(out, "{}", name)?;
+    for v in vector {
+        write!(out, " {}", format_float(*v as f64, 5))?;
+    }
+    writeln!(out)
+}
+
+fn load_model(path: &str) -> Result<FastText, String> {
+    let file = File::open(path).map_err(|err| format!("{}: {}", path, err))?;
+
+    FastText::from_reader(BufReader::new(file)).map_err(|err| format!("{}: {}", path, describe(err)))
+}
+
+/// A file of test data or the standard input, given as `-`.
+enum Input {
+    Stdin(BufReader<io::Stdin>),
+    File(BufReader<File>),
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            Input::Stdin(ref mut reader) => reader.read(buf),
+            Input::File(ref mut reader) => reader.read(buf),
+        }
+    }
+}
+
+impl BufRead for Input {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match *self {
+            Input::Stdin(ref mut reader) => reader.fill_buf(),
+            Input::File(ref mut reader) => reader.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match *self {
+            Input::Stdin(ref mut reader) => reader.consume(amt),
+            Input::File(ref mut reader) => reader.consume(amt),
+        }
+    }
+}
+
+fn open_input(path: &str) -> Result<Input, String> {
+    if path == "-" {
+        Ok(Input::Stdin(BufReader::new(io::stdin())))
+    } else {
+        let file = File::open(path).map_err(|err| format!("{}: {}", path, err))?;
+        Ok(Input::File(BufReader::new(file)))
+    }
+}
+
+fn parse_arg<T: ::std::str::FromStr>(args: &[String], i: usize, default: T, name: &str) -> Result<T, String> {
+    match args.get(i) {
+        Some(value) => value.parse().map_err(|_| format!("invalid {}: {}", name, value)),
+        None => Ok(default),
+    }
+}
+
+/// A line of `predict` output: the labels, each followed by its probability with `predict-prob`,
+/// printed with the default precision of C++ streams.
+fn format_predictions(predictions: &[Prediction], print_prob: bool) -> String {
+    let fields: Vec<String> = predictions.iter()
+        .map(|prediction| if print_prob {
+            format!("{} {}", prediction.label, format_float(prediction.probability as f64, 6))
+        } else {
+            prediction.label.clone()
+        })
+        .collect();
+
+    fields.join(" ")
+}
+
+fn predict(args: &[String], print_prob: bool) -> CliResult {
+    let command = if print_prob { "predict-prob" } else { "predict" };
+    if args.len() < 2 || args.len() > 4 {
+        return Err(usage_of(command, "<model> <test-data> [<k>] [<th>]"));
+    }
+
+    let model = load_model(&args[0])?;
+    let k = parse_arg(args, 2, 1usize, "k")?;
+    let threshold = parse_arg(args, 3, 0.0f32, "threshold")?;
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for predictions in model.predict_lines(open_input(&args[1])?, k, threshold) {
+        let predictions = predictions.map_err(io_err)?;
+        writeln!(out, "{}", format_predictions(&predictions, print_prob)).map_err(io_err)?;
+    }
+
+    Ok(())
+}
+
+fn test(args: &[String], per_label: bool) -> CliResult {
+    let command = if per_label { "test-label" } else { "test" };
+    if args.len() < 2 || args.len() > 4 {
+        return Err(usage_of(command, "<model> <test-data> [<k>] [<th>]"));
+    }
+
+    let model = load_model(&args[0])?;
+    let k = parse_arg(args, 2, 1usize, "k")?;
+    let threshold = parse_arg(args, 3, 0.0f32, "threshold")?;
+
+    let evaluation = model.test(open_input(&args[1])?, k, threshold).map_err(io_err)?;
+
+    if per_label {
+        for (label, metrics) in &evaluation.labels {
+            println!("F1-Score : {:.6}  Precision : {:.6}  Recall : {:.6}   {}",
+                     metrics.f1(), metrics.precision(), metrics.recall(), label);
+        }
+    }
+
+    println!("N\t{}", evaluation.examples);
+    println!("P@{}\t{}", k, format_float(evaluation.precision(), 3));
+    println!("R@{}\t{}", k, format_float(evaluation.recall(), 3));
+
+    Ok(())
+}
+
+fn print_word_vectors(args: &[String]) -> CliResult {
+    if args.len() != 1 {
+        return Err(usage_of("print-word-vectors", "<model>"));
+    }
+
+    let model = load_model(&args[0])?;
+    let mut vector = vec![0f32; model.dimension()];
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for line in stdin.lock().lines() {
+        let line = line.map_err(io_err)?;
+        for word in line.split_whitespace() {
+            model.word_vector_into(word, &mut vector).map_err(describe)?;
+            write_vector(&mut out, word, &vector).map_err(io_err)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn print_sentence_vectors(args: &[String]) -> CliResult {
+    if args.len() != 1 {
+        return Err(usage_of("print-sentence-vectors", "<model>"));
+    }
+
+    let model = load_model(&args[0])?;
+    let mut vector = vec![0f32; model.dimension()];
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for line in stdin.lock().lines() {
+        let line = line.map_err(io_err)?;
+        model.sentence_vector_into(&line, &mut vector).map_err(describe)?;
+
+        let values: Vec<String> = vector.iter().map(|v| format_float(*v as f64, 5)).collect();
+        writeln!(out, "{}", values.join(" ")).map_err(io_err)?;
+    }
+
+    Ok(())
+}
+
+fn print_ngrams(args: &[String]) -> CliResult {
+    if args.len() != 2 {
+        return Err(usage_of("print-ngrams", "<model> <word>"));
+    }
+
+    let model = load_model(&args[0])?;
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for (ngram, vector) in model.ngram_vectors(&args[1]) {
+        write_vector(&mut out, &ngram, &vector).map_err(io_err)?;
+    }
+
+    Ok(())
+}
+
+/// Reads queries from the standard input, prompting for each, until it is closed.
+fn queries<F: FnMut(&[&str]) -> CliResult>(prompt: &str, words: usize, mut query: F) -> CliResult {
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        print!("{}", prompt);
+        io::stdout().flush().map_err(io_err)?;
+
+        let line = match lines.next() {
+            Some(line) => line.map_err(io_err)?,
+            None => return Ok(()),
+        };
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.len() != words {
+            println!("Please provide {} word{}.", words, if words > 1 { "s" } else { "" });
+            continue;
+        }
+
+        query(&tokens)?;
+    }
+}
+
+fn print_neighbors(neighbors: &[(&str, f32)]) {
+    for &(word, similarity) in neighbors {
+        println!("{} {}", word, format_float(similarity as f64, 6));
+    }
+}
+
+fn nn(args: &[String]) -> CliResult {
+    if args.is_empty() || args.len() > 2 {
+        return Err(usage_of("nn", "<model> [<k>]"));
+    }
+
+    let model = load_model(&args[0])?;
+    let k = parse_arg(args, 1, 10usize, "k")?;
+    let index = ExactIndex::new(&model).map_err(|err| err.to_string())?;
+
+    queries("Query word? ", 1, |words| {
+        print_neighbors(&index.nearest_to_word(words[0], k));
+        Ok(())
+    })
+}
+
+fn analogies(args: &[String]) -> CliResult {
+    if args.is_empty() || args.len() > 2 {
+        return Err(usage_of("analogies", "<model> [<k>]"));
+    }
+
+    let model = load_model(&args[0])?;
+    let k = parse_arg(args, 1, 10usize, "k")?;
+    let index = ExactIndex::new(&model).map_err(|err| err.to_string())?;
+
+    queries("Query triplet (A - B + C)? ", 3, |words| {
+        print_neighbors(&index.analogies(words[0], words[1], words[2], k));
+        Ok(())
+    })
+}
+
+/// The training commands, which need the C++ library.
+#[cfg(not(feature = "pure-rust"))]
+mod training {
+    use std::fs::File;
+    use std::io::BufWriter;
+    use std::path::{Path, PathBuf};
+
+    use fasttext::format::{Loss, ModelKind};
+    use fasttext::train::{PretrainedVectors, QuantizeArgs, TrainArgs};
+    use fasttext::FastText;
+
+    use super::{describe, load_model, CliResult};
+
+    /// Options in the `-name value` form of fastText; flags take no value.
+    struct Options(Vec<(String, Option<String>)>);
+
+    const FLAGS: &'static [&'static str] = &["qnorm", "retrain", "qout"];
+
+    impl Options {
+        fn parse(args: &[String]) -> Result<Options, String> {
+            let mut options = Vec::new();
+            let mut args = args.iter();
+
+            while let Some(arg) = args.next() {
+                if !arg.starts_with('-') || arg.len() < 2 {
+                    return Err(format!("unexpected argument: {}", arg));
+                }
+
+                let name = arg[1..].to_owned();
+                if FLAGS.contains(&name.as_str()) {
+                    options.push((name, None));
+                } else {
+                    match args.next() {
+                        Some(value) => options.push((name, Some(value.clone()))),
+                        None => return Err(format!("missing value of -{}", name)),
+                    }
+                }
+            }
+
+            Ok(Options(options))
+        }
+
+        fn take(&mut self, name: &str) -> Option<String> {
+            let i = self.0.iter().rposition(|&(ref option, _)| option == name)?;
+            let (_, value) = self.0.remove(i);
+            self.0.retain(|&(ref option, _)| option != name);
+
+            Some(value.unwrap_or_default())
+        }
+
+        fn flag(&mut self, name: &str) -> bool {
+            self.take(name).is_some()
+        }
+
+        fn value<T: ::std::str::FromStr>(&mut self, name: &str, default: T) -> Result<T, String> {
+            match self.take(name) {
+                Some(value) => value.parse().map_err(|_| format!("invalid value of -{}: {}", name, value)),
+                None => Ok(default),
+            }
+        }
+
+        fn finish(self) -> CliResult {
+            match self.0.first() {
+                Some(&(ref name, _)) => Err(format!("unknown option: -{}", name)),
+                None => Ok(()),
+            }
+        }
+    }
+
+    fn parse_loss(name: &str) -> Result<Loss, String> {
+        match name {
+            "hs" => Ok(Loss::HierarchicalSoftmax),
+            "ns" => Ok(Loss::NegativeSampling),
+            "softmax" => Ok(Loss::Softmax),
+            _ => Err(format!("unknown loss: {}", name)),
+        }
+    }
+
+    pub fn train(args: &[String], kind: ModelKind) -> CliResult {
+        let mut options = Options::parse(args)?;
+
+        let input = options.take("input").ok_or_else(|| String::from("missing -input"))?;
+        let output = options.take("output").ok_or_else(|| String::from("missing -output"))?;
+
+        let defaults = match kind {
+            ModelKind::Supervised => TrainArgs::supervised(&input),
+            ModelKind::Skipgram => TrainArgs::skipgram(&input),
+            ModelKind::Cbow => TrainArgs::cbow(&input),
+        };
+
+        let loss = match options.take("loss") {
+            Some(name) => parse_loss(&name)?,
+            None => defaults.loss,
+        };
+
+        let train_args = TrainArgs {
+            loss,
+            lr: options.value("lr", defaults.lr)?,
+            lr_update_rate: options.value("lrUpdateRate", defaults.lr_update_rate)?,
+            dim: options.value("dim", defaults.dim)?,
+            ws: options.value("ws", defaults.ws)?,
+            epoch: options.value("epoch", defaults.epoch)?,
+            min_count: options.value("minCount", defaults.min_count)?,
+            min_count_label: options.value("minCountLabel", defaults.min_count_label)?,
+            neg: options.value("neg", defaults.neg)?,
+            word_ngrams: options.value("wordNgrams", defaults.word_ngrams)?,
+            bucket: options.value("bucket", defaults.bucket)?,
+            minn: options.value("minn", defaults.minn)?,
+            maxn: options.value("maxn", defaults.maxn)?,
+            thread: options.value("thread", defaults.thread)?,
+            t: options.value("t", defaults.t)?,
+            label: options.value("label", defaults.label.clone())?,
+            verbose: options.value("verbose", defaults.verbose)?,
+            seed: options.value("seed", defaults.seed)?,
+            pretrained_vectors: options.take("pretrainedVectors").map(|path| PretrainedVectors::File(PathBuf::from(path))),
+            ..defaults
+        };
+        options.finish()?;
+
+        let model = FastText::train(&train_args).map_err(|err| format!("{}: {}", input, describe(err)))?;
+        save_model(&model, &output, "bin")?;
+
+        let vec_path = format!("{}.vec", output);
+        let file = File::create(&vec_path).map_err(|err| format!("{}: {}", vec_path, err))?;
+        model.save_word2vec_text(BufWriter::new(file)).map_err(|err| format!("{}: {}", vec_path, err))
+    }
+
+    pub fn quantize(args: &[String]) -> CliResult {
+        let mut options = Options::parse(args)?;
+        let defaults = QuantizeArgs::default();
+
+        let output = options.take("output").ok_or_else(|| String::from("missing -output"))?;
+
+        let qargs = QuantizeArgs {
+            input: options.take("input").map(PathBuf::from),
+            cutoff: options.value("cutoff", defaults.cutoff)?,
+            retrain: options.flag("retrain"),
+            qnorm: options.flag("qnorm"),
+            qout: options.flag("qout"),
+            dsub: options.value("dsub", defaults.dsub)?,
+            epoch: options.value("epoch", defaults.epoch)?,
+            lr: options.value("lr", defaults.lr)?,
+            thread: options.value("thread", defaults.thread)?,
+            verbose: options.value("verbose", defaults.verbose)?,
+        };
+        options.finish()?;
+
+        if qargs.retrain && qargs.input.is_none() {
+            return Err(String::from("-retrain requires -input"));
+        }
+
+        let mut model = load_model(&format!("{}.bin", output))?;
+        model.quantize(&qargs).map_err(describe)?;
+        save_model(&model, &output, "ftz")
+    }
+
+    fn save_model(model: &FastText, output: &str, extension: &str) -> CliResult {
+        let path = format!("{}.{}", output, extension);
+
+        model.save_model(Path::new(&path)).map_err(|err| format!("{}: {}", path, describe(err)))
+    }
+
+    #[cfg(test)]
+    mod testing {
+        use super::*;
+
+        fn args(args: &[&str]) -> Vec<String> {
+            args.iter().map(|arg| arg.to_string()).collect()
+        }
+
+        #[test]
+        fn test_options_parse() {
+            let mut options = Options::parse(&args(&["-dim", "10", "-qnorm", "-lr", "0.5", "-dim", "20"])).unwrap();
+
+            assert_eq!(options.take("dim"), Some(String::from("20")), "check the last value wins");
+            assert_eq!(options.take("dim"), None, "check taken options are removed");
+            assert!(options.flag("qnorm"), "check flags");
+            assert!(!options.flag("qout"), "check missing flags");
+            assert_eq!(options.value("lr", 0.1).unwrap(), 0.5, "check values");
+            assert_eq!(options.value("epoch", 5).unwrap(), 5, "check defaults");
+            assert_eq!(options.finish(), Ok(()));
+
+            match Options::parse(&args(&["-dim"])) {
+                Err(err) => assert_eq!(err, "missing value of -dim"),
+                Ok(_) => assert!(false, "parsed an option without value"),
+            }
+            match Options::parse(&args(&["dim", "10"])) {
+                Err(err) => assert_eq!(err, "unexpected argument: dim"),
+                Ok(_) => assert!(false, "parsed an argument without dash"),
+            }
+        }
+
+        #[test]
+        fn test_options_errors() {
+            let mut options = Options::parse(&args(&["-epoch", "five", "-unknown", "1"])).unwrap();
+
+            assert_eq!(options.value("epoch", 5), Err(String::from("invalid value of -epoch: five")));
+            assert_eq!(options.finish(), Err(String::from("unknown option: -unknown")));
+            assert_eq!(parse_loss("ns"), Ok(Loss::NegativeSampling));
+            assert!(parse_loss("nce").is_err(), "check unknown loss");
+        }
+    }
+}
+
+#[cfg(not(feature = "pure-rust"))]
+use training::{quantize, train};
+
+#[cfg(feature = "pure-rust")]
+fn train(_args: &[String], _kind: ModelKind) -> CliResult {
+    Err(String::from("training is not supported by the pure-Rust backend"))
+}
+
+#[cfg(feature = "pure-rust")]
+fn quantize(_args: &[String]) -> CliResult {
+    Err(String::from("quantization is not supported by the pure-Rust backend"))
+}
+
+fn dump(args: &[String]) -> CliResult {
+    if args.len() != 2 {
+        return Err(usage_of("dump", "<model> <option>\n\n  <option> is one of: args, dict, input, output"));
+    }
+
+    let model = format::Model::open(Path::new(&args[0])).map_err(|err| format!("{}: {}", args[0], err))?;
+
+    let stdout = io::stdout();
+    dump_model(&model, &args[1], BufWriter::new(stdout.lock()))
+}
+
+fn dump_model<W: Write>(model: &format::Model, option: &str, mut out: W) -> CliResult {
+    match option {
+        "args" => model.dump_args(&mut out),
+        "dict" => model.dump_dictionary(&mut out),
+        "input" => model.dump_input(&mut out),
+        "output" => model.dump_output(&mut out),
+        option => return Err(format!("unknown dump option: {}", option)),
+    }.map_err(io_err)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => {
+            eprint!("{}", USAGE);
+            process::exit(1);
+        }
+    };
+
+    let result = match command {
+        "predict" => predict(args, false),
+        "predict-prob" => predict(args, true),
+        "print-word-vectors" => print_word_vectors(args),
+        "print-sentence-vectors" => print_sentence_vectors(args),
+        "print-ngrams" => print_ngrams(args),
+        "nn" => nn(args),
+        "analogies" => analogies(args),
+        "test" => test(args, false),
+        "test-label" => test(args, true),
+        "supervised" => train(args, ModelKind::Supervised),
+        "skipgram" => train(args, ModelKind::Skipgram),
+        "cbow" => train(args, ModelKind::Cbow),
+        "quantize" => quantize(args),
+        "dump" => dump(args),
+        _ => Err(String::from(USAGE)),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use fasttext::format::{Args, DenseMatrix, Dictionary, Loss, Matrix};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_arg() {
+        let args = args(&["model.bin", "3", "x"]);
+
+        assert_eq!(parse_arg(&args, 1, 1usize, "k"), Ok(3));
+        assert_eq!(parse_arg(&args, 3, 0.5f32, "threshold"), Ok(0.5), "check default");
+        assert_eq!(parse_arg(&args, 2, 0.0f32, "threshold"), Err(String::from("invalid threshold: x")));
+    }
+
+    #[test]
+    fn test_format_predictions() {
+        let predictions = vec![
+            Prediction { label: String::from("__label__a"), probability: 0.734512 },
+            Prediction { label: String::from("__label__b"), probability: 0.00001 },
+        ];
+
+        assert_eq!(format_predictions(&predictions, false), "__label__a __label__b");
+        assert_eq!(format_predictions(&predictions, true), "__label__a 0.734512 __label__b 1e-05");
+        assert_eq!(format_predictions(&[], true), "", "check no predictions");
+
+        let mut out = Vec::new();
+        write_vector(&mut out, "дуб", &[0.5, -0.125, 1.0 / 3.0]).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "дуб 0.5 -0.125 0.33333\n");
+    }
+
+    #[test]
+    fn test_dump_model() {
+        let model = format::Model {
+            version: 12,
+            args: Args {
+                dim: 2, ws: 5, epoch: 5, min_count: 1, neg: 5, word_ngrams: 1,
+                loss: Loss::Softmax, model: ModelKind::Supervised,
+                bucket: 0, minn: 0, maxn: 0, lr_update_rate: 100, t: 0.0001,
+            },
+            dictionary: Dictionary {
+                nwords: 0,
+                nlabels: 0,
+                ntokens: 0,
+                entries: Vec::new(),
+                pruneidx_size: -1,
+                pruneidx: Vec::new(),
+            },
+            input: Matrix::Dense(DenseMatrix { rows: 1, cols: 2, data: vec![0.25, -1.5] }),
+            qout: false,
+            output: Matrix::Dense(DenseMatrix { rows: 0, cols: 2, data: Vec::new() }),
+        };
+
+        let mut out = Vec::new();
+        dump_model(&model, "input", &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "1 2\n0.25 -1.5\n");
+
+        let mut out = Vec::new();
+        dump_model(&model, "dict", &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "0\n");
+
+        assert_eq!(dump_model(&model, "matrix", Vec::new()), Err(String::from("unknown dump option: matrix")));
+    }
+}
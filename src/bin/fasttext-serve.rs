@@ -0,0 +1,610 @@
+//! An HTTP server of models with JSON endpoints.
+//!
+//! Every model is served under `/models/<name>/`:
+//!
+//! - `POST predict` `{"text": .., "k": .., "threshold": ..}`
+//! - `POST predict-batch` `{"texts": [..], "k": .., "threshold": ..}`
+//! - `POST word-vector` `{"word": ..}`
+//! - `POST sentence-vector` `{"text": ..}`
+//! - `POST nn` `{"word": .., "k": ..}`
+//!
+//! `k` and `threshold` are optional and default to the `-k` and `-threshold` options.
+//! `GET /models` lists the models. Requests are served one at a time; bodies larger than `-max-body`
+//! are rejected with 413.
+//!
+//! With `-stdio predict` or `-stdio sentence-vector` it is a worker of one model instead: it reads
+//! `{"id": .., "text": ..}` lines on the standard input and writes `{"id": .., "predictions": [..]}`
+//! or `{"id": .., "vector": [..]}` lines, in the order of the input, on the standard output.
+
+extern crate fasttext;
+#[macro_use]
+extern crate serde_json;
+extern crate tiny_http;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::process;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde_json::Value;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use fasttext::ann::ExactIndex;
+use fasttext::FastText;
+
+const USAGE: &'static str = "usage: fasttext-serve [-addr <host:port>] [-k <k>] [-threshold <th>] [-max-body <bytes>] <name>=<model.bin>...
+       fasttext-serve -stdio <predict|sentence-vector> [-threads <n>] [-k <k>] [-threshold <th>] <model.bin>
+
+  -addr       the address to listen on [127.0.0.1:8000]
+  -k          the default number of labels predicted [1]
+  -threshold  the default probability threshold of predictions [0.0]
+  -nn         the default number of nearest neighbors [10]
+  -max-body   the maximum size of a request body in bytes [1048576]
+  -stdio      work on JSON lines of the standard input instead of serving HTTP
+  -threads    the number of worker threads of -stdio, each loading the model [1]
+
+A model given without a name is served under the stem of its file name; names must be unique.
+";
+
+/// The default of `-max-body`.
+const MAX_BODY: usize = 1 << 20;
+
+/// What a `-stdio` worker computes for each line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WorkerMode {
+    Predict,
+    SentenceVector,
+}
+
+struct Config {
+    addr: String,
+    k: usize,
+    threshold: f32,
+    nn: usize,
+    max_body: usize,
+    stdio: Option<WorkerMode>,
+    threads: usize,
+    models: Vec<(String, String)>,
+}
+
+impl Config {
+    fn parse(args: &[String]) -> Result<Config, String> {
+        let mut config = Config {
+            addr: String::from("127.0.0.1:8000"),
+            k: 1,
+            threshold: 0.0,
+            nn: 10,
+            max_body: MAX_BODY,
+            stdio: None,
+            threads: 1,
+            models: vec![],
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg.starts_with('-') {
+                let value = args.next().ok_or_else(|| format!("missing value of {}", arg))?;
+                let invalid = |_| format!("invalid value of {}: {}", arg, value);
+
+                match arg.as_str() {
+                    "-addr" => config.addr = value.clone(),
+                    "-k" => config.k = value.parse().map_err(invalid)?,
+                    "-threshold" => config.threshold = value.parse().map_err(|_| format!("invalid value of {}: {}", arg, value))?,
+                    "-nn" => config.nn = value.parse().map_err(invalid)?,
+                    "-max-body" => config.max_body = value.parse().map_err(invalid)?,
+                    "-threads" => config.threads = value.parse().map_err(invalid)?,
+                    "-stdio" => config.stdio = match value.as_str() {
+                        "predict" => Some(WorkerMode::Predict),
+                        "sentence-vector" => Some(WorkerMode::SentenceVector),
+                        _ => return Err(format!("unknown worker mode: {}", value)),
+                    },
+                    _ => return Err(format!("unknown option: {}", arg)),
+                }
+            } else {
+                let (name, path) = match arg.find('=') {
+                    Some(i) => (arg[..i].to_owned(), arg[i + 1..].to_owned()),
+                    None => {
+                        let stem = Path::new(arg).file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+                        (stem.to_owned(), arg.clone())
+                    }
+                };
+                if config.models.iter().any(|&(ref served, _)| *served == name) {
+                    return Err(format!("duplicate model name: {}", name));
+                }
+                config.models.push((name, path));
+            }
+        }
+
+        if config.models.is_empty() {
+            return Err(String::from("no model given"));
+        }
+        if config.stdio.is_some() && config.models.len() > 1 {
+            return Err(String::from("a worker takes one model"));
+        }
+
+        Ok(config)
+    }
+}
+
+fn load_model(path: &str) -> Result<FastText, String> {
+    let file = File::open(path).map_err(|err| format!("{}: {}", path, err))?;
+
+    FastText::from_reader(BufReader::new(file)).map_err(|err| format!("{}: {:?}", path, err))
+}
+
+/// A served model; its nearest-neighbor index is built on the first query.
+struct Served<'a> {
+    model: &'a FastText,
+    index: Option<ExactIndex<'a>>,
+}
+
+struct Service<'a> {
+    models: BTreeMap<String, Served<'a>>,
+    k: usize,
+    threshold: f32,
+    nn: usize,
+    max_body: usize,
+}
+
+/// An error response: a status code and a message.
+type Failure = (u16, String);
+
+fn bad_request<S: Into<String>>(msg: S) -> Failure {
+    (400, msg.into())
+}
+
+fn field<'v>(body: &'v Value, name: &str) -> Result<&'v str, Failure> {
+    body.get(name)
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| bad_request(format!("missing string field {:?}", name)))
+}
+
+impl<'a> Service<'a> {
+    fn k(&self, body: &Value, name: &str, default: usize) -> Result<usize, Failure> {
+        match body.get(name) {
+            None | Some(&Value::Null) => Ok(default),
+            Some(value) => value.as_u64()
+                .map(|k| k as usize)
+                .ok_or_else(|| bad_request(format!("{:?} must be a non-negative integer", name))),
+        }
+    }
+
+    fn threshold(&self, body: &Value) -> Result<f32, Failure> {
+        match body.get("threshold") {
+            None | Some(&Value::Null) => Ok(self.threshold),
+            Some(value) => value.as_f64()
+                .map(|threshold| threshold as f32)
+                .ok_or_else(|| bad_request("\"threshold\" must be a number")),
+        }
+    }
+
+    fn list(&self) -> Value {
+        Value::Array(self.models.iter()
+            .map(|(name, served)| json!({
+                "name": name,
+                "dim": served.model.dimension(),
+                "model": served.model.info().ok().map(|info| info.model().name()),
+            }))
+            .collect())
+    }
+
+    /// Handles a request of `method` on `url` with a JSON body.
+    fn handle(&mut self, method: &Method, url: &str, body: &str) -> Result<Value, Failure> {
+        let path: Vec<&str> = url.split('?').next().unwrap_or_default()
+            .split('/')
+            .filter(|part| !part.is_empty())
+            .collect();
+
+        if path.first() != Some(&"models") || path.len() == 2 || path.len() > 3 {
+            return Err((404, format!("not found: {}", url)));
+        }
+
+        match (method, path.len()) {
+            (&Method::Get, 1) => Ok(json!({"models": self.list()})),
+            (&Method::Post, 3) => {
+                let body: Value = serde_json::from_str(body).map_err(|err| bad_request(format!("invalid JSON: {}", err)))?;
+                self.call(path[1], path[2], &body)
+            }
+            _ => Err((405, String::from("method not allowed"))),
+        }
+    }
+
+    fn call(&mut self, name: &str, endpoint: &str, body: &Value) -> Result<Value, Failure> {
+        let (default_k, nn) = (self.k, self.nn);
+        let k = self.k(body, "k", if endpoint == "nn" { nn } else { default_k })?;
+        let threshold = self.threshold(body)?;
+
+        let served = self.models.get_mut(name).ok_or_else(|| (404, format!("unknown model: {}", name)))?;
+        let model = served.model;
+
+        match endpoint {
+            "predict" => {
+                let predictions = model.predict(field(body, "text")?, k, threshold).map_err(bad_request)?;
+                Ok(json!({"predictions": predictions}))
+            }
+            "predict-batch" => {
+                let texts = body.get("texts")
+                    .and_then(|texts| texts.as_array())
+                    .ok_or_else(|| bad_request("missing array field \"texts\""))?;
+
+                let mut batch = Vec::with_capacity(texts.len());
+                for text in texts {
+                    let text = text.as_str().ok_or_else(|| bad_request("\"texts\" must be strings"))?;
+                    batch.push(model.predict(text, k, threshold).map_err(bad_request)?);
+                }
+
+                Ok(json!({"predictions": batch}))
+            }
+            "word-vector" => {
+                let mut vector = vec![0f32; model.dimension()];
+                model.word_vector_into(field(body, "word")?, &mut vector).map_err(|err| (500, format!("{:?}", err)))?;
+
+                Ok(json!({"vector": vector}))
+            }
+            "sentence-vector" => {
+                let mut vector = vec![0f32; model.dimension()];
+                model.sentence_vector_into(field(body, "text")?, &mut vector).map_err(|err| (500, format!("{:?}", err)))?;
+
+                Ok(json!({"vector": vector}))
+            }
+            "nn" => {
+                let word = field(body, "word")?;
+                if served.index.is_none() {
+                    served.index = Some(ExactIndex::new(model).map_err(|err| (500, err.to_string()))?);
+                }
+                let index = served.index.as_ref().unwrap();
+
+                let neighbors: Vec<Value> = index.nearest_to_word(word, k).into_iter()
+                    .map(|(word, similarity)| json!({"word": word, "similarity": similarity}))
+                    .collect();
+
+                Ok(json!({"neighbors": neighbors}))
+            }
+            _ => Err((404, format!("unknown endpoint: {}", endpoint))),
+        }
+    }
+
+    /// Reads the body of `request`, up to `max_body` bytes.
+    fn body(&self, request: &mut Request) -> Result<String, Failure> {
+        let too_large = (413, format!("the request body exceeds {} bytes", self.max_body));
+        if request.body_length().map_or(false, |len| len > self.max_body) {
+            return Err(too_large);
+        }
+
+        let mut body = Vec::new();
+        request.as_reader().take(self.max_body as u64 + 1).read_to_end(&mut body)
+            .map_err(|err| bad_request(format!("failed to read the request: {}", err)))?;
+        if body.len() > self.max_body {
+            return Err(too_large);
+        }
+
+        String::from_utf8(body).map_err(|_| bad_request("the request body is not UTF-8"))
+    }
+
+    fn respond(&mut self, mut request: Request) {
+        let result = self.body(&mut request)
+            .and_then(|body| self.handle(request.method(), request.url(), &body));
+
+        let (status, value) = match result {
+            Ok(value) => (200, value),
+            Err((status, msg)) => (status, json!({"error": msg})),
+        };
+
+        let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+        let response = Response::from_string(value.to_string())
+            .with_status_code(status)
+            .with_header(header);
+
+        if let Err(err) = request.respond(response) {
+            eprintln!("failed to respond: {}", err);
+        }
+    }
+}
+
+/// Computes the output line of an input line of a worker.
+fn work(model: &FastText, mode: WorkerMode, k: usize, threshold: f32, line: &str) -> Value {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(err) => return json!({"id": Value::Null, "error": format!("invalid JSON: {}", err)}),
+    };
+
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let text = match request.get("text").and_then(|text| text.as_str()) {
+        Some(text) => text,
+        None => return json!({"id": id, "error": "missing string field \"text\""}),
+    };
+
+    match mode {
+        WorkerMode::Predict => match model.predict(text, k, threshold) {
+            Ok(predictions) => json!({"id": id, "predictions": predictions}),
+            Err(err) => json!({"id": id, "error": err}),
+        },
+        WorkerMode::SentenceVector => match model.sentence_vector(text) {
+            Some(vector) => json!({"id": id, "vector": vector}),
+            None => json!({"id": id, "error": "failed to compute the sentence vector"}),
+        },
+    }
+}
+
+/// Writes the results of the workers in the order of their input lines.
+fn write_ordered<W: Write>(out: W, results: Receiver<(u64, String)>) -> io::Result<()> {
+    let mut out = BufWriter::new(out);
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+
+    for (i, result) in results {
+        pending.insert(i, result);
+
+        while let Some(result) = pending.remove(&next) {
+            writeln!(out, "{}", result)?;
+            next += 1;
+        }
+    }
+
+    out.flush()
+}
+
+/// Runs a thread per model, as a model can't be used by several threads at once.
+fn run_worker(models: Vec<FastText>, mode: WorkerMode, config: &Config) -> Result<(), String> {
+    let threads = models.len();
+
+    let (jobs, job_queue): (SyncSender<(u64, String)>, _) = mpsc::sync_channel(threads * 64);
+    let job_queue = Arc::new(Mutex::new(job_queue));
+    let (results, result_queue) = mpsc::sync_channel(threads * 64);
+
+    let workers: Vec<_> = models.into_iter()
+        .map(|model| {
+            let (job_queue, results) = (job_queue.clone(), results.clone());
+            let (k, threshold) = (config.k, config.threshold);
+
+            thread::spawn(move || loop {
+                let job = job_queue.lock().unwrap().recv();
+                let (i, line) = match job {
+                    Ok(job) => job,
+                    Err(_) => return,
+                };
+
+                let result = work(&model, mode, k, threshold, &line).to_string();
+                if results.send((i, result)).is_err() {
+                    return;
+                }
+            })
+        })
+        .collect();
+    drop(results);
+
+    let writer = thread::spawn(move || write_ordered(io::stdout(), result_queue));
+
+    let stdin = io::stdin();
+    let mut read = Ok(());
+    for (i, line) in stdin.lock().lines().enumerate() {
+        match line {
+            Ok(line) => if jobs.send((i as u64, line)).is_err() {
+                break;
+            },
+            Err(err) => {
+                read = Err(err.to_string());
+                break;
+            }
+        }
+    }
+    drop(jobs);
+
+    for worker in workers {
+        worker.join().map_err(|_| String::from("a worker panicked"))?;
+    }
+
+    writer.join()
+        .map_err(|_| String::from("the writer panicked"))?
+        .map_err(|err| err.to_string())?;
+
+    read
+}
+
+fn run(config: Config) -> Result<(), String> {
+    if let Some(mode) = config.stdio {
+        let mut models = Vec::with_capacity(config.threads.max(1));
+        for _ in 0..config.threads.max(1) {
+            models.push(load_model(&config.models[0].1)?);
+        }
+        return run_worker(models, mode, &config);
+    }
+
+    let mut models = Vec::with_capacity(config.models.len());
+    for &(_, ref path) in &config.models {
+        models.push(load_model(path)?);
+    }
+
+    let mut service = Service {
+        models: config.models.iter()
+            .zip(&models)
+            .map(|(&(ref name, _), model)| (name.clone(), Served { model, index: None }))
+            .collect(),
+        k: config.k,
+        threshold: config.threshold,
+        nn: config.nn,
+        max_body: config.max_body,
+    };
+
+    let server = Server::http(config.addr.as_str()).map_err(|err| format!("{}: {}", config.addr, err))?;
+    eprintln!("serving {} model(s) on {}", models.len(), config.addr);
+
+    for request in server.incoming_requests() {
+        service.respond(request);
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let config = match Config::parse(&args) {
+        Ok(config) => config,
+        Err(err) => {
+            eprint!("{}\n\n{}", err, USAGE);
+            process::exit(1);
+        }
+    };
+
+    if let Err(err) = run(config) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use std::net::TcpStream;
+    #[cfg(not(feature = "pure-rust"))]
+    use fasttext::train::TrainArgs;
+
+    fn service<'a>() -> Service<'a> {
+        Service { models: BTreeMap::new(), k: 1, threshold: 0.0, nn: 10, max_body: 64 }
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
+    fn supervised_model() -> Option<FastText> {
+        let args = TrainArgs { verbose: 0, ..TrainArgs::supervised("./supervised_text.txt").deterministic(0) };
+        Some(FastText::train(&args).unwrap())
+    }
+
+    /// The model of `test-data`, as the pure-Rust backend can't train; tests are skipped without it.
+    #[cfg(feature = "pure-rust")]
+    fn supervised_model() -> Option<FastText> {
+        let path = "./test-data/supervised_model.bin";
+        if !Path::new(path).exists() {
+            eprintln!("skipped: {} is missing, see the readme", path);
+            return None;
+        }
+
+        Some(load_model(path).unwrap())
+    }
+
+    #[test]
+    fn test_serve_routes() {
+        let mut service = service();
+
+        match service.handle(&Method::Get, "/models", "") {
+            Ok(value) => assert_eq!(value.get("models").and_then(|models| models.as_array()).map(|models| models.len()), Some(0),
+                                    "check empty model list"),
+            Err(err) => assert!(false, "failed to list models: {:?}", err),
+        }
+
+        for &(ref method, url, status) in &[
+            (Method::Post, "/models/unknown/predict", 404),
+            (Method::Get, "/models/unknown/predict", 405),
+            (Method::Get, "/unknown", 404),
+        ] {
+            match service.handle(method, url, "{\"text\": \"дуб\"}") {
+                Err((code, _)) => assert_eq!(code, status, "check status of {:?} {}", method, url),
+                Ok(value) => assert!(false, "{:?} {} succeeded: {}", method, url, value),
+            }
+        }
+
+        match service.handle(&Method::Post, "/models/unknown/predict", "{") {
+            Err((400, _)) => assert!(true),
+            res => assert!(false, "invalid JSON was accepted: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_serve_config() {
+        let args: Vec<String> = ["-k", "3", "sup=a/supervised.bin", "b/unsupervised.bin"].iter().map(|s| s.to_string()).collect();
+
+        match Config::parse(&args) {
+            Ok(config) => {
+                assert_eq!(config.k, 3, "check k");
+                assert_eq!(config.models, vec![
+                    (String::from("sup"), String::from("a/supervised.bin")),
+                    (String::from("unsupervised"), String::from("b/unsupervised.bin")),
+                ], "check models");
+            }
+            Err(err) => assert!(false, "failed to parse arguments: {}", err),
+        }
+
+        assert!(Config::parse(&[]).is_err(), "check a model is required");
+
+        let args: Vec<String> = ["-stdio", "predict", "a.bin", "b.bin"].iter().map(|s| s.to_string()).collect();
+        assert!(Config::parse(&args).is_err(), "check a worker takes one model");
+
+        let args: Vec<String> = ["a/model.bin", "model=b/supervised.bin"].iter().map(|s| s.to_string()).collect();
+        match Config::parse(&args) {
+            Err(err) => assert_eq!(err, "duplicate model name: model"),
+            Ok(config) => assert!(false, "duplicate names were accepted: {:?}", config.models),
+        }
+
+        let args: Vec<String> = ["-max-body", "1024", "a.bin"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(Config::parse(&args).map(|config| config.max_body), Ok(1024), "check max body");
+    }
+
+    /// Sends a raw HTTP request to the server and returns the status line of the response.
+    fn send(service: &mut Service, server: &Server, request: String) -> String {
+        let addr = server.server_addr();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(request.as_bytes()).unwrap();
+
+            let mut status = String::new();
+            BufReader::new(stream).read_line(&mut status).unwrap();
+            status
+        });
+
+        service.respond(server.recv().unwrap());
+
+        client.join().unwrap().trim_end().to_owned()
+    }
+
+    #[test]
+    fn test_serve_http() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let mut service = service();
+
+        let list = String::from("GET /models HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+        assert_eq!(send(&mut service, &server, list), "HTTP/1.1 200 OK", "check a listing");
+
+        let body = format!("{{\"text\": \"{}\"}}", "дуб ".repeat(16));
+        let predict = format!("POST /models/unknown/predict HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+        assert_eq!(send(&mut service, &server, predict), "HTTP/1.1 413 Payload Too Large", "check a large body is rejected");
+
+        let unknown = String::from("POST /models/unknown/predict HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: 2\r\n\r\n{}");
+        assert_eq!(send(&mut service, &server, unknown), "HTTP/1.1 404 Not Found", "check an unknown model");
+    }
+
+    #[test]
+    fn test_serve_work() {
+        let model = match supervised_model() {
+            Some(model) => model,
+            None => return,
+        };
+
+        let result = work(&model, WorkerMode::Predict, 2, 0.0, "{\"id\": 7, \"text\": \"Куда сходить вечером\"}");
+        assert_eq!(result.get("id").and_then(|id| id.as_u64()), Some(7), "check id");
+        assert_eq!(result.get("predictions").and_then(|p| p.as_array()).map(|p| p.len()), Some(2), "check predictions");
+
+        let result = work(&model, WorkerMode::SentenceVector, 1, 0.0, "{\"id\": \"a\", \"text\": \"Доброе утро\"}");
+        assert_eq!(result.get("vector").and_then(|v| v.as_array()).map(|v| v.len()), Some(model.dimension()), "check vector");
+
+        let result = work(&model, WorkerMode::Predict, 1, 0.0, "{\"id\": 1}");
+        assert!(result.get("error").is_some(), "check a line without text fails");
+    }
+
+    #[test]
+    fn test_serve_write_ordered() {
+        let (results, queue) = mpsc::sync_channel(8);
+        for &i in &[2u64, 0, 3, 1] {
+            results.send((i, i.to_string())).unwrap();
+        }
+        drop(results);
+
+        let mut out = Vec::new();
+        write_ordered(&mut out, queue).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "0\n1\n2\n3\n", "check order of results");
+    }
+}
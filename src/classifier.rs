@@ -0,0 +1,243 @@
+//! Classification with supervised models, as the `predict` and `test` commands of fastText do.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+
+use format::ModelKind;
+use predict::Predict;
+use FastText;
+
+/// The prefix of labels in training and test data.
+pub const LABEL_PREFIX: &'static str = "__label__";
+
+/// A label predicted for a text.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Prediction {
+    pub label: String,
+    pub probability: f32,
+}
+
+/// The counts of one label over a test set.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LabelMetrics {
+    /// The examples labeled with it.
+    pub gold: u64,
+    /// The examples it was predicted for.
+    pub predicted: u64,
+    /// The examples it was predicted for and labeled with.
+    pub correct: u64,
+}
+
+impl LabelMetrics {
+    pub fn precision(&self) -> f64 {
+        ratio(self.correct, self.predicted)
+    }
+
+    pub fn recall(&self) -> f64 {
+        ratio(self.correct, self.gold)
+    }
+
+    pub fn f1(&self) -> f64 {
+        let (precision, recall) = (self.precision(), self.recall());
+
+        if precision + recall > 0.0 {
+            2.0 * precision * recall / (precision + recall)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The result of testing a model, `Meter` in fastText.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Evaluation {
+    pub examples: u64,
+    pub gold: u64,
+    pub predicted: u64,
+    pub correct: u64,
+    pub labels: BTreeMap<String, LabelMetrics>,
+}
+
+impl Evaluation {
+    /// Precision at `k`: the share of predictions which are right.
+    pub fn precision(&self) -> f64 {
+        ratio(self.correct, self.predicted)
+    }
+
+    /// Recall at `k`: the share of gold labels which are predicted.
+    pub fn recall(&self) -> f64 {
+        ratio(self.correct, self.gold)
+    }
+
+    pub fn label(&self, label: &str) -> Option<&LabelMetrics> {
+        self.labels.get(label)
+    }
+
+    pub(crate) fn add(&mut self, gold: &[&str], predictions: &[Prediction]) {
+        self.examples += 1;
+        self.gold += gold.len() as u64;
+        self.predicted += predictions.len() as u64;
+
+        for prediction in predictions {
+            let metrics = self.labels.entry(prediction.label.clone()).or_insert_with(LabelMetrics::default);
+            metrics.predicted += 1;
+
+            if gold.contains(&prediction.label.as_str()) {
+                metrics.correct += 1;
+                self.correct += 1;
+            }
+        }
+
+        for label in gold {
+            self.labels.entry(label.to_string()).or_insert_with(LabelMetrics::default).gold += 1;
+        }
+    }
+}
+
+/// An iterator over the predictions for the lines of a reader, see `FastText::predict_lines`.
+pub struct PredictLines<'a, R> {
+    model: &'a FastText,
+    reader: R,
+    buffer: Vec<u8>,
+    k: usize,
+    threshold: f32,
+}
+
+impl<'a, R: BufRead> Iterator for PredictLines<'a, R> {
+    type Item = io::Result<Vec<Prediction>>;
+
+    fn next(&mut self) -> Option<io::Result<Vec<Prediction>>> {
+        self.buffer.clear();
+
+        match self.reader.read_until(b'\n', &mut self.buffer) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(err) => return Some(Err(err)),
+        }
+
+        while self.buffer.last() == Some(&b'\n') || self.buffer.last() == Some(&b'\r') {
+            self.buffer.pop();
+        }
+
+        if self.buffer.contains(&0) {
+            return Some(Err(io::Error::new(io::ErrorKind::InvalidData, "the line contains a NUL byte")));
+        }
+
+        let threshold = self.threshold;
+        Some(self.model.0.predict_bytes(&mut self.buffer, self.k as i32)
+            .map(|predict| predictions(&predict, threshold))
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err)))
+    }
+}
+
+fn predictions(predict: &Predict, threshold: f32) -> Vec<Prediction> {
+    predict.as_slice().iter()
+        .map(|record| Prediction {
+            label: record.word().to_owned(),
+            probability: record.prediction().exp(),
+        })
+        .filter(|prediction| prediction.probability >= threshold)
+        .collect()
+}
+
+fn ratio(num: u64, den: u64) -> f64 {
+    if den > 0 {
+        num as f64 / den as f64
+    } else {
+        0.0
+    }
+}
+
+impl FastText {
+    /// The `k` most probable labels of `text` with a probability of at least `threshold`,
+    /// most probable first.
+    pub fn predict(&self, text: &str, k: usize, threshold: f32) -> Result<Vec<Prediction>, String> {
+        self.0.predict(text, k as i32).map(|predict| predictions(&predict, threshold))
+    }
+
+    /// Predicts the labels of every line of `reader`, one item per line, as `fasttext predict` does.
+    ///
+    /// Lines are read into one buffer which is reused, so input of any size is streamed. A line containing
+    /// a NUL byte gives an `InvalidData` error.
+    pub fn predict_lines<R: BufRead>(&self, reader: R, k: usize, threshold: f32) -> PredictLines<R> {
+        PredictLines {
+            model: self,
+            reader,
+            buffer: Vec::new(),
+            k,
+            threshold,
+        }
+    }
+
+    /// Tests the model on lines of labels and texts, as `fasttext test` does.
+    ///
+    /// As fastText, labels unknown to the model are ignored, and lines without known labels or
+    /// without words are skipped.
+    pub fn test<R: BufRead>(&self, reader: R, k: usize, threshold: f32) -> io::Result<Evaluation> {
+        let info = self.info().map_err(|err| io::Error::new(io::ErrorKind::Other, format!("{:?}", err)))?;
+        if info.model() != ModelKind::Supervised {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Model needs to be supervised for prediction!"));
+        }
+
+        let dict = self.0.get_dictionary();
+        let mut evaluation = Evaluation::default();
+
+        for line in reader.lines() {
+            let line = line?;
+            let (mut labels, words): (Vec<&str>, Vec<&str>) = line.split_whitespace()
+                .partition(|token| token.starts_with(LABEL_PREFIX));
+            labels.retain(|label| dict.word_index(label).is_some());
+
+            if labels.is_empty() || words.is_empty() {
+                continue;
+            }
+
+            let predictions = self.predict(&line, k, threshold)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            evaluation.add(&labels, &predictions);
+        }
+
+        Ok(evaluation)
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    fn prediction(label: &str) -> Prediction {
+        Prediction { label: label.to_owned(), probability: 1.0 }
+    }
+
+    #[test]
+    fn test_evaluation_add() {
+        let mut evaluation = Evaluation::default();
+
+        evaluation.add(&["__label__a", "__label__b"], &[prediction("__label__a")]);
+        evaluation.add(&["__label__b"], &[prediction("__label__a")]);
+
+        assert_eq!(evaluation.examples, 2, "check examples");
+        assert_eq!(evaluation.precision(), 0.5, "check precision");
+        assert!((evaluation.recall() - 1.0 / 3.0).abs() < 1e-9, "check recall");
+
+        assert_eq!(evaluation.label("__label__a"), Some(&LabelMetrics { gold: 1, predicted: 2, correct: 1 }));
+        assert_eq!(evaluation.label("__label__b"), Some(&LabelMetrics { gold: 2, predicted: 0, correct: 0 }));
+        assert_eq!(evaluation.label("__label__b").unwrap().f1(), 0.0, "check f1 without predictions");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_evaluation_serde() {
+        let mut evaluation = Evaluation::default();
+        evaluation.add(&["__label__a"], &[prediction("__label__a"), prediction("__label__b")]);
+
+        let json = ::serde_json::to_string(&evaluation).unwrap();
+        match ::serde_json::from_str::<Evaluation>(&json) {
+            Ok(decoded) => assert_eq!(decoded, evaluation, "check round trip"),
+            Err(err) => assert!(false, "failed to decode {}: {}", json, err),
+        }
+    }
+}
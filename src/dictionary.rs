@@ -1,4 +1,5 @@
-use libc::{c_int, c_void};
+use libc::{c_int, c_long, c_void};
+use std::ffi::CString;
 use std::os::raw::c_char;
 use string::WrapperString;
 
@@ -11,6 +12,8 @@ extern "C" {
     fn DICT_Find(wrapper: *const WrapperDictionary, word: *const c_char) -> c_int;
     fn DICT_GetWord(wrapper: *const WrapperDictionary, index: c_int, word: *mut WrapperString);
     fn DICT_WordsCount(wrapper: *const WrapperDictionary) -> c_int;
+    fn DICT_WordCounts(wrapper: *const WrapperDictionary, counts: *mut c_long);
+    fn DICT_TokensCount(wrapper: *const WrapperDictionary) -> c_long;
 }
 
 #[derive(Debug)]
@@ -22,7 +25,8 @@ impl Dictionary{
     }
 
     pub fn word_index(&self, word: &str) -> Option<i64> {
-        let index = unsafe { DICT_Find(self.0, word.as_ptr() as *const c_char) };
+        let word = CString::new(word).unwrap_or_default();
+        let index = unsafe { DICT_Find(self.0, word.as_ptr()) };
         if index >= 0 {
             Some(index as i64)
         } else {
@@ -55,4 +59,22 @@ impl Dictionary{
             DICT_WordsCount(self.0)
         }
     }
+
+    /// The number of occurrences of each word in the training data, by word index.
+    pub fn word_counts(&self) -> Vec<i64> {
+        let mut counts = vec![0 as c_long; self.words_count() as usize];
+
+        unsafe {
+            DICT_WordCounts(self.0, counts.as_mut_ptr());
+        }
+
+        counts.into_iter().map(|count| count as i64).collect()
+    }
+
+    /// The number of tokens in the training data.
+    pub fn tokens_count(&self) -> i64 {
+        unsafe {
+            DICT_TokensCount(self.0) as i64
+        }
+    }
 }
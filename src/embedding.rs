@@ -0,0 +1,247 @@
+//! Owned embeddings with the usual vector arithmetic.
+//!
+//! Binary operations on embeddings of different dimensions panic.
+
+use std::ops::{Add, AddAssign, Deref, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use vector::Vector;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Embedding(Vec<f32>);
+
+impl Embedding {
+    pub fn new(data: Vec<f32>) -> Embedding {
+        Embedding(data)
+    }
+
+    pub fn zeros(dim: usize) -> Embedding {
+        Embedding(vec![0f32; dim])
+    }
+
+    pub fn dim(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        self.0.as_slice()
+    }
+
+    pub fn into_vec(self) -> Vec<f32> {
+        self.0
+    }
+
+    pub fn dot(&self, other: &Embedding) -> f32 {
+        check_dim(self, other);
+
+        self.0.iter().zip(&other.0).map(|(a, b)| a * b).sum()
+    }
+
+    /// The L2 norm.
+    pub fn norm(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// The cosine similarity, 0 if one of the embeddings is zero.
+    pub fn cosine(&self, other: &Embedding) -> f32 {
+        let norms = self.norm() * other.norm();
+
+        if norms > 0.0 {
+            self.dot(other) / norms
+        } else {
+            0.0
+        }
+    }
+
+    pub fn euclidean(&self, other: &Embedding) -> f32 {
+        check_dim(self, other);
+
+        self.0.iter().zip(&other.0).map(|(a, b)| (a - b) * (a - b)).sum::<f32>().sqrt()
+    }
+
+    /// Scales the embedding to the unit L2 norm; a zero embedding stays zero.
+    pub fn normalize(&mut self) {
+        let norm = self.norm();
+
+        if norm > 0.0 {
+            for v in self.0.iter_mut() {
+                *v /= norm;
+            }
+        }
+    }
+
+    pub fn normalized(mut self) -> Embedding {
+        self.normalize();
+
+        self
+    }
+}
+
+fn check_dim(a: &Embedding, b: &Embedding) {
+    assert_eq!(a.dim(), b.dim(), "embeddings of different dimensions");
+}
+
+impl Deref for Embedding {
+    type Target = [f32];
+
+    fn deref(&self) -> &[f32] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[f32]> for Embedding {
+    fn as_ref(&self) -> &[f32] {
+        self.as_slice()
+    }
+}
+
+impl From<Vec<f32>> for Embedding {
+    fn from(data: Vec<f32>) -> Embedding {
+        Embedding(data)
+    }
+}
+
+impl<'a> From<&'a [f32]> for Embedding {
+    fn from(data: &'a [f32]) -> Embedding {
+        Embedding(data.to_owned())
+    }
+}
+
+impl<'a> From<Vector<'a>> for Embedding {
+    fn from(vector: Vector<'a>) -> Embedding {
+        Embedding(vector.as_slice().to_owned())
+    }
+}
+
+impl From<Embedding> for Vec<f32> {
+    fn from(embedding: Embedding) -> Vec<f32> {
+        embedding.0
+    }
+}
+
+impl<'a> AddAssign<&'a Embedding> for Embedding {
+    fn add_assign(&mut self, other: &'a Embedding) {
+        check_dim(self, other);
+
+        for (a, b) in self.0.iter_mut().zip(&other.0) {
+            *a += *b;
+        }
+    }
+}
+
+impl<'a> SubAssign<&'a Embedding> for Embedding {
+    fn sub_assign(&mut self, other: &'a Embedding) {
+        check_dim(self, other);
+
+        for (a, b) in self.0.iter_mut().zip(&other.0) {
+            *a -= *b;
+        }
+    }
+}
+
+impl MulAssign<f32> for Embedding {
+    fn mul_assign(&mut self, a: f32) {
+        for v in self.0.iter_mut() {
+            *v *= a;
+        }
+    }
+}
+
+impl<'a> Add<&'a Embedding> for Embedding {
+    type Output = Embedding;
+
+    fn add(mut self, other: &'a Embedding) -> Embedding {
+        self += other;
+
+        self
+    }
+}
+
+impl<'a, 'b> Add<&'b Embedding> for &'a Embedding {
+    type Output = Embedding;
+
+    fn add(self, other: &'b Embedding) -> Embedding {
+        self.clone() + other
+    }
+}
+
+impl<'a> Sub<&'a Embedding> for Embedding {
+    type Output = Embedding;
+
+    fn sub(mut self, other: &'a Embedding) -> Embedding {
+        self -= other;
+
+        self
+    }
+}
+
+impl<'a, 'b> Sub<&'b Embedding> for &'a Embedding {
+    type Output = Embedding;
+
+    fn sub(self, other: &'b Embedding) -> Embedding {
+        self.clone() - other
+    }
+}
+
+impl Mul<f32> for Embedding {
+    type Output = Embedding;
+
+    fn mul(mut self, a: f32) -> Embedding {
+        self *= a;
+
+        self
+    }
+}
+
+impl<'a> Mul<f32> for &'a Embedding {
+    type Output = Embedding;
+
+    fn mul(self, a: f32) -> Embedding {
+        self.clone() * a
+    }
+}
+
+impl Neg for Embedding {
+    type Output = Embedding;
+
+    fn neg(self) -> Embedding {
+        self * -1.0
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_embedding_similarity() {
+        let a = Embedding::new(vec![3.0, 4.0]);
+        let b = Embedding::new(vec![4.0, -3.0]);
+
+        assert_eq!(a.dot(&b), 0.0);
+        assert_eq!(a.norm(), 5.0);
+        assert_eq!(a.cosine(&b), 0.0);
+        assert_eq!(a.cosine(&(&a * 2.0)), 1.0);
+        assert_eq!(a.cosine(&Embedding::zeros(2)), 0.0);
+        assert_eq!(a.euclidean(&b), 50f32.sqrt());
+        assert_eq!(a.clone().normalized(), Embedding::new(vec![0.6, 0.8]));
+        assert_eq!(Embedding::zeros(2).normalized(), Embedding::zeros(2));
+    }
+
+    #[test]
+    fn test_embedding_arithmetic() {
+        let a = Embedding::new(vec![1.0, 2.0]);
+        let b = Embedding::from(&[0.5f32, -1.0][..]);
+
+        assert_eq!(&a + &b, Embedding::new(vec![1.5, 1.0]));
+        assert_eq!(&a - &b, Embedding::new(vec![0.5, 3.0]));
+        assert_eq!(a.clone() - &b + &b, a);
+        assert_eq!(-a.clone(), Embedding::new(vec![-1.0, -2.0]));
+        assert_eq!(Vec::from(a * 2.0), vec![2.0, 4.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_embedding_dimension_mismatch() {
+        let _ = Embedding::zeros(2).dot(&Embedding::zeros(3));
+    }
+}
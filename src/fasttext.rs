@@ -1,43 +1,184 @@
+use std::io::{self, Read};
 use std::path::Path;
 use std::os::raw::c_char;
 use std::os::unix::ffi::OsStrExt;
+use std::slice;
 use std::str;
-use std::ffi::CString;
-use libc::{c_int, c_void};
+use std::ffi::{CStr, CString};
+use libc::{c_double, c_float, c_int, c_long, c_short, c_ulong, c_void};
 
 use dictionary::WrapperDictionary;
 use dictionary::Dictionary;
 use predict::{WrapperPredictResult, Predict};
 use vector::{WrapperWordVector, Vector};
+use format::{Args, Loss, ModelKind};
+use info::ModelInfo;
+use sentence::SentenceMode;
+use train::{PretrainedVectors, QuantizeArgs, TrainArgs};
 
-pub const RES_OK: ResSuccess = ResSuccess(0);
+pub use result::{Err, ResSuccess, RES_OK};
 
-#[derive(Debug, Clone, Copy)]
-pub struct ResSuccess(i32);
+unsafe fn to_ptr_const_char(path: &Path) -> CString {
+    CString::new(path.as_os_str().as_bytes()).unwrap_or_default()
+}
 
-#[derive(Debug, Clone, Copy)]
-pub enum Err {
-    ResErrorNotOpen,
-    RerErrorWrongModel,
-    ResErrorModelNotInit,
-    ResErrorExecution,
+fn to_c_string(text: &str) -> CString {
+    CString::new(text).unwrap_or_default()
 }
 
-unsafe fn to_ptr_const_char(path: &Path) -> CString {
-    CString::new(path.as_os_str().as_bytes()).unwrap_or_default()
+fn to_c_path(path: Option<&Path>) -> Option<CString> {
+    path.map(|path| unsafe { to_ptr_const_char(path) })
+}
+
+fn c_str_or_null(text: &Option<CString>) -> *const c_char {
+    text.as_ref().map_or(::std::ptr::null(), |text| text.as_ptr())
+}
+
+/// Calls `f` with the C arguments of `args`, which live as long as the call.
+fn with_wrapper_args<F: FnOnce(&WrapperArgs) -> c_int>(args: &TrainArgs, f: F) -> c_int {
+    let input = unsafe { to_ptr_const_char(&args.input) };
+    let label = to_c_string(&args.label);
+    // models are staged to files by FastText::train
+    let pretrained_vectors = to_c_path(match args.pretrained_vectors {
+        Some(PretrainedVectors::File(ref path)) => Some(path.as_path()),
+        _ => None,
+    });
+
+    let wrapper_args = WrapperArgs {
+        input: input.as_ptr(),
+        label: label.as_ptr(),
+        pretrained_vectors: c_str_or_null(&pretrained_vectors),
+        lr: args.lr,
+        lr_update_rate: args.lr_update_rate,
+        dim: args.dim,
+        ws: args.ws,
+        epoch: args.epoch,
+        min_count: args.min_count,
+        min_count_label: args.min_count_label,
+        neg: args.neg,
+        word_ngrams: args.word_ngrams,
+        loss: args.loss.to_i32(),
+        model: args.model.to_i32(),
+        bucket: args.bucket,
+        minn: args.minn,
+        maxn: args.maxn,
+        thread: args.thread,
+        t: args.t,
+        verbose: args.verbose,
+        cutoff: 0,
+        retrain: 0,
+        qnorm: 0,
+        qout: 0,
+        dsub: 2,
+        seed: args.seed,
+    };
+
+    f(&wrapper_args)
+}
+
+type ReadCallback = extern "C" fn(reader: *mut c_void, buf: *mut c_char, len: c_ulong) -> c_long;
+
+extern "C" fn read_callback<R: Read>(reader: *mut c_void, buf: *mut c_char, len: c_ulong) -> c_long {
+    let reader = unsafe { &mut *(reader as *mut R) };
+    let buf = unsafe { slice::from_raw_parts_mut(buf as *mut u8, len as usize) };
+
+    loop {
+        match reader.read(buf) {
+            Ok(n) => return n as c_long,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => return -1,
+        }
+    }
+}
+
+type NgramCallback = extern "C" fn(ctx: *mut c_void, ngram: *const c_char, vec: *const c_float, len: c_int);
+
+extern "C" fn ngram_callback(ctx: *mut c_void, ngram: *const c_char, vec: *const c_float, len: c_int) {
+    let ngrams = unsafe { &mut *(ctx as *mut Vec<(String, Vec<f32>)>) };
+    let ngram = unsafe { CStr::from_ptr(ngram) }.to_string_lossy().into_owned();
+    let vec = unsafe { slice::from_raw_parts(vec, len as usize) };
+
+    ngrams.push((ngram, vec.to_vec()));
 }
 
 #[repr(C)]
 pub(crate) struct FastTextWrapper(*mut c_void);
 
+#[repr(C)]
+struct WrapperArgs {
+    input: *const c_char,
+    label: *const c_char,
+    pretrained_vectors: *const c_char,
+    lr: c_double,
+    lr_update_rate: c_int,
+    dim: c_int,
+    ws: c_int,
+    epoch: c_int,
+    min_count: c_int,
+    min_count_label: c_int,
+    neg: c_int,
+    word_ngrams: c_int,
+    loss: c_int,
+    model: c_int,
+    bucket: c_int,
+    minn: c_int,
+    maxn: c_int,
+    thread: c_int,
+    t: c_double,
+    verbose: c_int,
+    cutoff: c_int,
+    retrain: c_short,
+    qnorm: c_short,
+    qout: c_short,
+    dsub: c_int,
+    seed: c_int,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct WrapperModelInfo {
+    version: c_int,
+    dim: c_int,
+    ws: c_int,
+    epoch: c_int,
+    min_count: c_int,
+    neg: c_int,
+    word_ngrams: c_int,
+    loss: c_int,
+    model: c_int,
+    bucket: c_int,
+    minn: c_int,
+    maxn: c_int,
+    lr_update_rate: c_int,
+    t: c_double,
+    nwords: c_int,
+    nlabels: c_int,
+    ntokens: c_long,
+    quantized: c_short,
+}
+
 extern "C" {
     fn NewFastText() -> *mut c_void;
     fn FT_LoadModel(wrapper: *mut c_void, model_path: *const c_char) -> c_int;
+    fn FT_LoadModelMapped(wrapper: *mut c_void, model_path: *const c_char) -> c_int;
+    fn FT_LoadModelReader(wrapper: *mut c_void, reader: *mut c_void, read: ReadCallback) -> c_int;
     fn FT_LoadVectors(wrapper: *mut c_void, vectors_path: *const c_char) -> c_int;
     fn FT_GetDictionary(wrapper: *const c_void) -> *const WrapperDictionary;
     fn FT_GetWordVector(wrapper: *const c_void, word: *const c_char) -> *mut WrapperWordVector;
     fn FT_GetSentenceVector(wrapper: *const c_void, text: *const c_char) -> *mut WrapperWordVector;
+    fn FT_GetModelInfo(wrapper: *const c_void, info: *mut WrapperModelInfo) -> c_int;
+    fn FT_GetDimension(wrapper: *const c_void) -> c_int;
+    fn FT_GetWordVectorInto(wrapper: *const c_void, word: *const c_char, out: *mut c_float, len: c_int) -> c_int;
+    fn FT_GetSentenceVectorInto(wrapper: *const c_void, text: *const c_char, out: *mut c_float, len: c_int) -> c_int;
+    fn FT_EmbedWords(wrapper: *const c_void, words: *const *const c_char, n: c_int, out: *mut c_float, threads: c_int) -> c_int;
+    fn FT_EmbedSentences(wrapper: *const c_void, texts: *const *const c_char, n: c_int, out: *mut c_float, threads: c_int) -> c_int;
+    fn FT_GetSentenceVectorMode(wrapper: *const c_void, text: *const c_char, supervised: c_short) -> *mut WrapperWordVector;
     fn FT_Predict(wrapper: *const c_void, text: *const c_char, count: c_int) -> *const WrapperPredictResult;
+    fn FT_Train(wrapper: *mut c_void, args: *const WrapperArgs) -> c_int;
+    fn FT_FineTune(wrapper: *mut c_void, base: *const c_void, args: *const WrapperArgs) -> c_int;
+    fn FT_Quantize(wrapper: *mut c_void, args: *const WrapperArgs) -> c_int;
+    fn FT_SaveModel(wrapper: *const c_void, path: *const c_char) -> c_int;
+    fn FT_GetNgramVectors(wrapper: *const c_void, word: *const c_char, ctx: *mut c_void, callback: NgramCallback);
     fn FT_Release(wrapper: *mut c_void);
 }
 
@@ -49,6 +190,10 @@ impl Default for FastTextWrapper {
     }
 }
 
+// The C++ model can move to another thread, but isn't `Sync`: depending on the fastText version,
+// predictions and sentence vectors go through buffers and a random generator of the model.
+unsafe impl Send for FastTextWrapper {}
+
 impl Drop for FastTextWrapper {
     fn drop(&mut self) {
         unsafe {
@@ -68,6 +213,26 @@ impl FastTextWrapper {
         }
     }
 
+    pub(crate) fn load_model_mapped(&mut self, model_path: &Path) -> Result<ResSuccess, Err> {
+        unsafe {
+            match FT_LoadModelMapped(self.0, to_ptr_const_char(model_path).as_ptr() as *const c_char) {
+                0 => Ok(RES_OK),
+                2 => Err(Err::RerErrorWrongModel),
+                _ => Err(Err::ResErrorNotOpen),
+            }
+        }
+    }
+
+    pub(crate) fn load_model_from_reader<R: Read>(&mut self, reader: &mut R) -> Result<ResSuccess, Err> {
+        unsafe {
+            match FT_LoadModelReader(self.0, reader as *mut R as *mut c_void, read_callback::<R>) {
+                0 => Ok(RES_OK),
+                2 => Err(Err::RerErrorWrongModel),
+                _ => Err(Err::ResErrorNotOpen),
+            }
+        }
+    }
+
     pub(crate) fn load_vectors(&mut self, vectors_path: &Path) -> Result<ResSuccess, Err> {
         unsafe {
             match FT_LoadVectors(self.0, to_ptr_const_char(vectors_path).as_ptr() as *const c_char) {
@@ -82,7 +247,7 @@ impl FastTextWrapper {
     }
 
     pub(crate) fn word_to_vector(&self, word: &str) -> Option<Vector> {
-        let vec = unsafe { Vector::new(FT_GetWordVector(self.0, word.as_ptr() as *const c_char)) };
+        let vec = unsafe { Vector::new(FT_GetWordVector(self.0, to_c_string(word).as_ptr())) };
 
         if !vec.is_empty() {
             Some(vec)
@@ -92,7 +257,111 @@ impl FastTextWrapper {
     }
 
     pub(crate) fn sentence_to_vector(&self, text: &str) -> Option<Vector> {
-        let vec = unsafe { Vector::new(FT_GetSentenceVector(self.0, text.as_ptr() as *const c_char)) };
+        let vec = unsafe { Vector::new(FT_GetSentenceVector(self.0, to_c_string(text).as_ptr())) };
+
+        if !vec.is_empty() {
+            Some(vec)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn model_info(&self) -> Result<ModelInfo, Err> {
+        let mut info = WrapperModelInfo::default();
+
+        match unsafe { FT_GetModelInfo(self.0, &mut info) } {
+            0 => (),
+            3 => return Err(Err::ResErrorModelNotInit),
+            _ => return Err(Err::ResErrorExecution),
+        }
+
+        Ok(ModelInfo {
+            version: info.version,
+            nwords: info.nwords,
+            nlabels: info.nlabels,
+            ntokens: info.ntokens as i64,
+            quantized: info.quantized != 0,
+            args: Args {
+                dim: info.dim,
+                ws: info.ws,
+                epoch: info.epoch,
+                min_count: info.min_count,
+                neg: info.neg,
+                word_ngrams: info.word_ngrams,
+                loss: Loss::from_i32(info.loss).map_err(|_| Err::RerErrorWrongModel)?,
+                model: ModelKind::from_i32(info.model).map_err(|_| Err::RerErrorWrongModel)?,
+                bucket: info.bucket,
+                minn: info.minn,
+                maxn: info.maxn,
+                lr_update_rate: info.lr_update_rate,
+                t: info.t,
+            },
+        })
+    }
+
+    /// The dimension of the model, 0 before a model is loaded.
+    pub(crate) fn dimension(&self) -> usize {
+        self.checked_dimension().unwrap_or_default()
+    }
+
+    fn checked_dimension(&self) -> Result<usize, Err> {
+        match unsafe { FT_GetDimension(self.0) } {
+            dim if dim >= 0 => Ok(dim as usize),
+            _ => Err(Err::ResErrorModelNotInit),
+        }
+    }
+
+    pub(crate) fn word_vector_into(&self, word: &str, out: &mut [f32]) -> Result<ResSuccess, Err> {
+        if out.len() != self.checked_dimension()? {
+            return Err(Err::ResErrorWrongDimension);
+        }
+
+        match unsafe { FT_GetWordVectorInto(self.0, to_c_string(word).as_ptr(), out.as_mut_ptr(), out.len() as c_int) } {
+            0 => Ok(RES_OK),
+            3 => Err(Err::ResErrorModelNotInit),
+            _ => Err(Err::ResErrorExecution),
+        }
+    }
+
+    pub(crate) fn sentence_vector_into(&self, text: &str, out: &mut [f32]) -> Result<ResSuccess, Err> {
+        if out.len() != self.checked_dimension()? {
+            return Err(Err::ResErrorWrongDimension);
+        }
+
+        match unsafe { FT_GetSentenceVectorInto(self.0, to_c_string(text).as_ptr(), out.as_mut_ptr(), out.len() as c_int) } {
+            0 => Ok(RES_OK),
+            3 => Err(Err::ResErrorModelNotInit),
+            _ => Err(Err::ResErrorExecution),
+        }
+    }
+
+    /// Writes the vectors of `words` into the rows of `out`, of `words.len()` times the model dimension.
+    pub(crate) fn embed_words(&self, words: &[&str], out: &mut [f32], threads: usize) -> Result<ResSuccess, Err> {
+        let words: Vec<CString> = words.iter().map(|word| to_c_string(word)).collect();
+        let ptrs: Vec<*const c_char> = words.iter().map(|word| word.as_ptr()).collect();
+
+        match unsafe { FT_EmbedWords(self.0, ptrs.as_ptr(), ptrs.len() as c_int, out.as_mut_ptr(), threads as c_int) } {
+            0 => Ok(RES_OK),
+            3 => Err(Err::ResErrorModelNotInit),
+            _ => Err(Err::ResErrorExecution),
+        }
+    }
+
+    /// Writes the vectors of `texts` into the rows of `out`, of `texts.len()` times the model dimension.
+    pub(crate) fn embed_sentences(&self, texts: &[&str], out: &mut [f32], threads: usize) -> Result<ResSuccess, Err> {
+        let texts: Vec<CString> = texts.iter().map(|text| to_c_string(text)).collect();
+        let ptrs: Vec<*const c_char> = texts.iter().map(|text| text.as_ptr()).collect();
+
+        match unsafe { FT_EmbedSentences(self.0, ptrs.as_ptr(), ptrs.len() as c_int, out.as_mut_ptr(), threads as c_int) } {
+            0 => Ok(RES_OK),
+            3 => Err(Err::ResErrorModelNotInit),
+            _ => Err(Err::ResErrorExecution),
+        }
+    }
+
+    pub(crate) fn sentence_to_vector_with(&self, text: &str, mode: SentenceMode) -> Option<Vector> {
+        let supervised = (mode == SentenceMode::Supervised) as c_short;
+        let vec = unsafe { Vector::new(FT_GetSentenceVectorMode(self.0, to_c_string(text).as_ptr(), supervised)) };
 
         if !vec.is_empty() {
             Some(vec)
@@ -102,11 +371,109 @@ impl FastTextWrapper {
     }
 
     pub(crate) fn predict(&self, text: &str, count: i32) -> Result<Predict, String> {
+        let predict = unsafe { Predict::new(FT_Predict(self.0, to_c_string(text).as_ptr(), count as c_int)) };
+
+        match predict.err() {
+            Ok(_) => Ok(predict),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Same as `predict`, but terminates `text` in place instead of copying it to a `CString`.
+    pub(crate) fn predict_bytes(&self, text: &mut Vec<u8>, count: i32) -> Result<Predict, String> {
+        if text.contains(&0) {
+            return Err(String::from("the text contains a NUL byte"));
+        }
+        text.push(0);
+
         let predict = unsafe { Predict::new(FT_Predict(self.0, text.as_ptr() as *const c_char, count as c_int)) };
+        text.pop();
 
         match predict.err() {
             Ok(_) => Ok(predict),
             Err(err) => Err(err),
         }
     }
+
+    pub(crate) fn train(&mut self, args: &TrainArgs) -> Result<ResSuccess, Err> {
+        match with_wrapper_args(args, |wrapper_args| unsafe { FT_Train(self.0, wrapper_args) }) {
+            0 => Ok(RES_OK),
+            1 => Err(Err::ResErrorNotOpen),
+            _ => Err(Err::ResErrorExecution),
+        }
+    }
+
+    /// Trains on `args.input` starting from the vectors of the supervised model `base`.
+    pub(crate) fn fine_tune(&mut self, base: &FastTextWrapper, args: &TrainArgs) -> Result<ResSuccess, Err> {
+        match with_wrapper_args(args, |wrapper_args| unsafe { FT_FineTune(self.0, base.0, wrapper_args) }) {
+            0 => Ok(RES_OK),
+            1 => Err(Err::ResErrorNotOpen),
+            2 => Err(Err::RerErrorWrongModel),
+            3 => Err(Err::ResErrorModelNotInit),
+            _ => Err(Err::ResErrorExecution),
+        }
+    }
+
+    pub(crate) fn quantize(&mut self, args: &QuantizeArgs) -> Result<ResSuccess, Err> {
+        let info = self.model_info()?;
+        let input = to_c_path(args.input.as_ref().map(|path| path.as_path()));
+        let label = to_c_string("__label__");
+
+        // fastText keeps the training arguments of the model and takes only these from quantize.
+        let wrapper_args = WrapperArgs {
+            input: c_str_or_null(&input),
+            label: label.as_ptr(),
+            pretrained_vectors: ::std::ptr::null(),
+            lr: args.lr,
+            lr_update_rate: info.args.lr_update_rate,
+            dim: info.args.dim,
+            ws: info.args.ws,
+            epoch: args.epoch,
+            min_count: info.args.min_count,
+            min_count_label: 0,
+            neg: info.args.neg,
+            word_ngrams: info.args.word_ngrams,
+            loss: info.args.loss.to_i32(),
+            model: info.args.model.to_i32(),
+            bucket: info.args.bucket,
+            minn: info.args.minn,
+            maxn: info.args.maxn,
+            thread: args.thread,
+            t: info.args.t,
+            verbose: args.verbose,
+            cutoff: args.cutoff as c_int,
+            retrain: args.retrain as c_short,
+            qnorm: args.qnorm as c_short,
+            qout: args.qout as c_short,
+            dsub: args.dsub,
+            seed: 0,
+        };
+
+        match unsafe { FT_Quantize(self.0, &wrapper_args) } {
+            0 => Ok(RES_OK),
+            1 => Err(Err::ResErrorNotOpen),
+            2 => Err(Err::RerErrorWrongModel),
+            3 => Err(Err::ResErrorModelNotInit),
+            _ => Err(Err::ResErrorExecution),
+        }
+    }
+
+    pub(crate) fn save_model(&self, path: &Path) -> Result<ResSuccess, Err> {
+        match unsafe { FT_SaveModel(self.0, to_ptr_const_char(path).as_ptr()) } {
+            0 => Ok(RES_OK),
+            3 => Err(Err::ResErrorModelNotInit),
+            _ => Err(Err::ResErrorNotOpen),
+        }
+    }
+
+    pub(crate) fn ngram_vectors(&self, word: &str) -> Vec<(String, Vec<f32>)> {
+        let mut ngrams: Vec<(String, Vec<f32>)> = Vec::new();
+
+        unsafe {
+            FT_GetNgramVectors(self.0, to_c_string(word).as_ptr(),
+                               &mut ngrams as *mut Vec<(String, Vec<f32>)> as *mut c_void, ngram_callback);
+        }
+
+        ngrams
+    }
 }
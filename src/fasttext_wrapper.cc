@@ -1,8 +1,15 @@
+#include <algorithm>
+#include <functional>
 #include <iostream>
+#include <random>
 #include <sstream>
+#include <thread>
+#include <vector>
 #include <stdio.h>
 #include <string.h>
 #include <math.h>
+#include <fcntl.h>
+#include <sys/mman.h>
 #include <sys/stat.h>
 #include <unistd.h>
 #include "fasttext/src/fasttext.h"
@@ -11,6 +18,54 @@ const int RES_OK = 0;
 const int RES_ERROR_NOT_OPEN = 1;
 const int RES_ERROR_WRONG_MODEL = 2;
 const int RES_ERROR_NOT_INIT = 3;
+const int RES_ERROR_EXECUTION = 4;
+
+bool checkModelFile(std::istream& in, int32_t& version);
+
+class FastTextModel : public fasttext::FastText {
+public:
+    FastTextModel() : fileVersion_(FASTTEXT_VERSION) {}
+
+    int loadMapped(const char* data, size_t size);
+
+    void sentenceVector(std::istream& in, fasttext::Vector& svec, bool supervised);
+
+    const fasttext::Args& args() const {
+        return *args_;
+    }
+
+    bool isQuantized() const {
+        return quant_;
+    }
+
+    int32_t fileVersion() const {
+        return fileVersion_;
+    }
+
+    void setFileVersion(int32_t version) {
+        fileVersion_ = version;
+    }
+
+    void saveModelTo(std::ostream& out);
+
+    void trainSeeded(const fasttext::Args& args, int32_t seed);
+
+    void fineTune(const FastTextModel& base, const fasttext::Args& args, int32_t seed);
+
+    void ngrams(const std::string& word, std::vector<int32_t>& ngrams, std::vector<std::string>& substrings) const {
+        dict_->getSubwords(word, ngrams, substrings);
+    }
+
+    void inputRow(fasttext::Vector& vec, int32_t id) const {
+        vec.zero();
+        addInputVector(vec, id);
+    }
+
+private:
+    std::shared_ptr<fasttext::Matrix> mapMatrix(std::istream& in, const char* data, size_t size);
+
+    int32_t fileVersion_;
+};
 
 extern "C" {
     struct WrapperDictionary {
@@ -18,13 +73,65 @@ extern "C" {
     };
 
     struct WrapperFastText {
-        fasttext::FastText *model;
+        FastTextModel *model;
+        void          *mapped;
+        size_t         mapped_size;
     };
 
     struct WrapperVector {
         fasttext::Vector *vector;
     };
 
+    struct WrapperArgs {
+        const char* input;
+        const char* label;
+        const char* pretrained_vectors;
+        double      lr;
+        int         lr_update_rate;
+        int         dim;
+        int         ws;
+        int         epoch;
+        int         min_count;
+        int         min_count_label;
+        int         neg;
+        int         word_ngrams;
+        int         loss;
+        int         model;
+        int         bucket;
+        int         minn;
+        int         maxn;
+        int         thread;
+        double      t;
+        int         verbose;
+        int         cutoff;
+        short       retrain;
+        short       qnorm;
+        short       qout;
+        int         dsub;
+        int         seed;
+    };
+
+    struct WrapperModelInfo {
+        int    version;
+        int    dim;
+        int    ws;
+        int    epoch;
+        int    min_count;
+        int    neg;
+        int    word_ngrams;
+        int    loss;
+        int    model;
+        int    bucket;
+        int    minn;
+        int    maxn;
+        int    lr_update_rate;
+        double t;
+        int    nwords;
+        int    nlabels;
+        long   ntokens;
+        short  quantized;
+    };
+
     struct WrapperString {
         char*        str;
         unsigned int len;
@@ -46,6 +153,425 @@ extern "C" {
     };
 }
 
+typedef long (*ReadCallback)(void* reader, char* buf, unsigned long len);
+typedef void (*NgramCallback)(void* ctx, const char* ngram, const float* vec, int len);
+
+class ReaderStreamBuf : public std::streambuf {
+public:
+    ReaderStreamBuf(void* reader, ReadCallback read) : reader_(reader), read_(read), failed_(false), buffer_(64 * 1024) {}
+
+    bool failed() const {
+        return failed_;
+    }
+
+protected:
+    int_type underflow() override {
+        if (gptr() < egptr()) {
+            return traits_type::to_int_type(*gptr());
+        }
+
+        const long n = read_(reader_, buffer_.data(), buffer_.size());
+        if (n < 0) {
+            failed_ = true;
+        }
+        if (n <= 0) {
+            return traits_type::eof();
+        }
+
+        setg(buffer_.data(), buffer_.data(), buffer_.data() + n);
+
+        return traits_type::to_int_type(*gptr());
+    }
+
+private:
+    void*             reader_;
+    ReadCallback      read_;
+    bool              failed_;
+    std::vector<char> buffer_;
+};
+
+class MemoryStreamBuf : public std::streambuf {
+public:
+    MemoryStreamBuf(const char* data, size_t size) {
+        char* begin = const_cast<char*>(data);
+        setg(begin, begin, begin + size);
+    }
+
+protected:
+    pos_type seekoff(off_type off, std::ios_base::seekdir dir, std::ios_base::openmode which) override {
+        char* pos = dir == std::ios_base::beg ? eback() : (dir == std::ios_base::cur ? gptr() : egptr());
+        pos += off;
+
+        if (pos < eback() || pos > egptr()) {
+            return pos_type(off_type(-1));
+        }
+
+        setg(eback(), pos, egptr());
+
+        return pos_type(pos - eback());
+    }
+
+    pos_type seekpos(pos_type pos, std::ios_base::openmode which) override {
+        return seekoff(off_type(pos), std::ios_base::beg, which);
+    }
+};
+
+std::shared_ptr<fasttext::Matrix> FastTextModel::mapMatrix(std::istream& in, const char* data, size_t size) {
+    int64_t m, n;
+
+    in.read((char*)&m, sizeof(int64_t));
+    in.read((char*)&n, sizeof(int64_t));
+    if (in.fail() || m < 0 || n < 0) {
+        return nullptr;
+    }
+
+    // m and n come from the file: their product must neither wrap around nor go past its end.
+    const size_t offset = size_t(in.tellg());
+    const size_t count = size_t(m) * size_t(n);
+    if ((n != 0 && count / size_t(n) != size_t(m)) || count > (size - offset) / sizeof(fasttext::real)) {
+        return nullptr;
+    }
+    const size_t len = count * sizeof(fasttext::real);
+
+    // The matrices follow the variable-length strings of the dictionary: a misaligned one is copied.
+    if (offset % alignof(fasttext::real) != 0) {
+        auto matrix = std::make_shared<fasttext::Matrix>(m, n);
+        memcpy(matrix->data_, data + offset, len);
+
+        in.seekg(len, std::ios_base::cur);
+
+        return matrix;
+    }
+
+    // The matrix only borrows the mapping, so its data must not reach Matrix's delete[].
+    auto matrix = std::shared_ptr<fasttext::Matrix>(new fasttext::Matrix(), [](fasttext::Matrix* matrix) {
+        matrix->data_ = nullptr;
+        delete matrix;
+    });
+    matrix->m_ = m;
+    matrix->n_ = n;
+    matrix->data_ = (fasttext::real*)(data + offset);
+
+    in.seekg(len, std::ios_base::cur);
+
+    return matrix;
+}
+
+int FastTextModel::loadMapped(const char* data, size_t size) {
+    MemoryStreamBuf buf(data, size);
+    std::istream in(&buf);
+
+    int32_t version;
+    if (!checkModelFile(in, version)) {
+        return RES_ERROR_WRONG_MODEL;
+    }
+
+    auto args = std::make_shared<fasttext::Args>();
+    args->load(in);
+
+    auto dict = std::make_shared<fasttext::Dictionary>(args, in);
+
+    bool quant_input;
+    in.read((char*)&quant_input, sizeof(bool));
+    if (in.fail() || quant_input) {
+        return RES_ERROR_WRONG_MODEL;
+    }
+
+    auto input = mapMatrix(in, data, size);
+    if (input == nullptr) {
+        return RES_ERROR_WRONG_MODEL;
+    }
+
+    in.read((char*)&args->qout, sizeof(bool));
+
+    auto output = mapMatrix(in, data, size);
+    if (output == nullptr) {
+        return RES_ERROR_WRONG_MODEL;
+    }
+
+    args_ = args;
+    dict_ = dict;
+    input_ = input;
+    output_ = output;
+    quant_ = false;
+    fileVersion_ = version;
+
+    model_ = std::make_shared<fasttext::Model>(input_, output_, args_, 0);
+    if (args_->model == fasttext::model_name::sup) {
+        model_->setTargetCounts(dict_->getCounts(fasttext::entry_type::label));
+    } else {
+        model_->setTargetCounts(dict_->getCounts(fasttext::entry_type::word));
+    }
+
+    return RES_OK;
+}
+
+// The two branches of FastText::getSentenceVector, selected by the caller instead of the model kind.
+void FastTextModel::sentenceVector(std::istream& in, fasttext::Vector& svec, bool supervised) {
+    svec.zero();
+
+    if (supervised) {
+        std::vector<int32_t> line, labels;
+        dict_->getLine(in, line, labels);
+
+        for (size_t i = 0; i < line.size(); i++) {
+            addInputVector(svec, line[i]);
+        }
+        if (!line.empty()) {
+            svec.mul(1.0 / line.size());
+        }
+    } else {
+        fasttext::Vector vec(args_->dim);
+        std::string sentence;
+        std::getline(in, sentence);
+
+        std::istringstream iss(sentence);
+        std::string word;
+        int32_t count = 0;
+
+        while (iss >> word) {
+            getWordVector(vec, word);
+
+            fasttext::real norm = vec.norm();
+            if (norm > 0) {
+                vec.mul(1.0 / norm);
+                svec.addVector(vec);
+                count++;
+            }
+        }
+        if (count > 0) {
+            svec.mul(1.0 / count);
+        }
+    }
+}
+
+// Points a fasttext::Vector to a borrowed buffer, giving the vector its own storage back when
+// destroyed, also when an exception is thrown.
+class BorrowedVector {
+public:
+    BorrowedVector(fasttext::Vector& vec, float* data, int64_t size)
+        : vec_(vec), own_data_(vec.data_), own_size_(vec.m_) {
+        vec_.data_ = data;
+        vec_.m_ = size;
+    }
+
+    ~BorrowedVector() {
+        vec_.data_ = own_data_;
+        vec_.m_ = own_size_;
+    }
+
+    BorrowedVector(const BorrowedVector&) = delete;
+    BorrowedVector& operator=(const BorrowedVector&) = delete;
+
+private:
+    fasttext::Vector& vec_;
+    fasttext::real* own_data_;
+    int64_t own_size_;
+};
+
+// Runs f on a fasttext::Vector viewing the caller's buffer, so that lookups don't allocate vectors.
+// The buffer is never freed by the thread-local vector.
+template <typename F>
+void withBorrowedVector(float* data, int64_t size, F f) {
+    static thread_local fasttext::Vector vec(0);
+
+    BorrowedVector borrowed(vec, data, size);
+    f(vec);
+}
+
+// Runs f on the rows [begin, end) of the n rows split between the given number of threads.
+// An exception thrown by f is caught in its thread; the result is RES_ERROR_EXECUTION if any f threw.
+template <typename F>
+int forEachRow(int n, int threads, F f) {
+    auto run = [&f](int begin, int end, int& res) {
+        try {
+            f(begin, end);
+            res = RES_OK;
+        } catch(std::exception &e) {
+            res = RES_ERROR_EXECUTION;
+        }
+    };
+
+    if (threads <= 1 || n <= 1) {
+        int res;
+        run(0, n, res);
+        return res;
+    }
+
+    const int chunk = (n + threads - 1) / threads;
+    std::vector<int> results((n + chunk - 1) / chunk, RES_OK);
+    std::vector<std::thread> pool;
+
+    for (int begin = 0, i = 0; begin < n; begin += chunk, i++) {
+        pool.push_back(std::thread(run, begin, std::min(begin + chunk, n), std::ref(results[i])));
+    }
+    for (auto& thread : pool) {
+        thread.join();
+    }
+
+    for (int res : results) {
+        if (res != RES_OK) {
+            return res;
+        }
+    }
+
+    return RES_OK;
+}
+
+// FastText::saveModel writing to any stream instead of the path built from args_->output.
+void FastTextModel::saveModelTo(std::ostream& out) {
+    signModel(out);
+    args_->save(out);
+    dict_->save(out);
+
+    out.write((char*)&(quant_), sizeof(bool));
+    if (quant_) {
+        qinput_->save(out);
+    } else {
+        input_->save(out);
+    }
+
+    out.write((char*)&(args_->qout), sizeof(bool));
+    if (quant_ && args_->qout) {
+        qoutput_->save(out);
+    } else {
+        output_->save(out);
+    }
+}
+
+// Matrix::uniform with a given seed instead of 1.
+void uniformMatrix(fasttext::Matrix& matrix, fasttext::real a, int32_t seed) {
+    std::minstd_rand rng(seed);
+    std::uniform_real_distribution<> uniform(-a, a);
+
+    for (int64_t i = 0; i < matrix.m_ * matrix.n_; i++) {
+        matrix.data_[i] = uniform(rng);
+    }
+}
+
+// FastText::train initializing the input matrix from seed. Training with one thread is then
+// reproducible, as the threads seed their own generators with their index.
+void FastTextModel::trainSeeded(const fasttext::Args& args, int32_t seed) {
+    args_ = std::make_shared<fasttext::Args>(args);
+    dict_ = std::make_shared<fasttext::Dictionary>(args_);
+
+    std::ifstream ifs(args_->input);
+    dict_->readFromFile(ifs);
+    ifs.close();
+
+    if (!args_->pretrainedVectors.empty()) {
+        loadVectors(args_->pretrainedVectors);
+    } else {
+        input_ = std::make_shared<fasttext::Matrix>(dict_->nwords() + args_->bucket, args_->dim);
+        uniformMatrix(*input_, 1.0 / args_->dim, seed);
+    }
+
+    bool supervised = args_->model == fasttext::model_name::sup;
+    output_ = std::make_shared<fasttext::Matrix>(supervised ? dict_->nlabels() : dict_->nwords(), args_->dim);
+    output_->zero();
+
+    quant_ = false;
+    startThreads();
+
+    model_ = std::make_shared<fasttext::Model>(input_, output_, args_, 0);
+    if (supervised) {
+        model_->setTargetCounts(dict_->getCounts(fasttext::entry_type::label));
+    } else {
+        model_->setTargetCounts(dict_->getCounts(fasttext::entry_type::word));
+    }
+}
+
+void copyRow(const fasttext::Matrix& from, int64_t i, fasttext::Matrix& to, int64_t j) {
+    std::copy(from.data_ + i * from.n_, from.data_ + (i + 1) * from.n_, to.data_ + j * to.n_);
+}
+
+// FastText::train starting from the matrices of base instead of random ones: the rows of the words,
+// buckets and labels base knows are copied, the others are initialized as train does.
+// The dimension and the bucket count of args must be those of base.
+void FastTextModel::fineTune(const FastTextModel& base, const fasttext::Args& args, int32_t seed) {
+    args_ = std::make_shared<fasttext::Args>(args);
+    dict_ = std::make_shared<fasttext::Dictionary>(args_);
+
+    std::ifstream ifs(args_->input);
+    dict_->readFromFile(ifs);
+    ifs.close();
+
+    input_ = std::make_shared<fasttext::Matrix>(dict_->nwords() + args_->bucket, args_->dim);
+    uniformMatrix(*input_, 1.0 / args_->dim, seed);
+    for (int32_t i = 0; i < dict_->nwords(); i++) {
+        int32_t id = base.dict_->getId(dict_->getWord(i));
+        if (id >= 0 && id < base.dict_->nwords()) {
+            copyRow(*base.input_, id, *input_, i);
+        }
+    }
+    for (int32_t i = 0; i < args_->bucket; i++) {
+        copyRow(*base.input_, base.dict_->nwords() + i, *input_, dict_->nwords() + i);
+    }
+
+    output_ = std::make_shared<fasttext::Matrix>(dict_->nlabels(), args_->dim);
+    output_->zero();
+    for (int32_t i = 0; i < dict_->nlabels(); i++) {
+        int32_t id = base.dict_->getId(dict_->getLabel(i));
+        if (id >= base.dict_->nwords()) {
+            copyRow(*base.output_, id - base.dict_->nwords(), *output_, i);
+        }
+    }
+
+    quant_ = false;
+    startThreads();
+
+    model_ = std::make_shared<fasttext::Model>(input_, output_, args_, 0);
+    model_->setTargetCounts(dict_->getCounts(fasttext::entry_type::label));
+}
+
+fasttext::Args makeArgs(const struct WrapperArgs* wrapper) {
+    fasttext::Args args;
+
+    if (wrapper->input != nullptr) {
+        args.input = std::string(wrapper->input);
+    }
+    if (wrapper->label != nullptr) {
+        args.label = std::string(wrapper->label);
+    }
+    if (wrapper->pretrained_vectors != nullptr) {
+        args.pretrainedVectors = std::string(wrapper->pretrained_vectors);
+    }
+
+    args.lr = wrapper->lr;
+    args.lrUpdateRate = wrapper->lr_update_rate;
+    args.dim = wrapper->dim;
+    args.ws = wrapper->ws;
+    args.epoch = wrapper->epoch;
+    args.minCount = wrapper->min_count;
+    args.minCountLabel = wrapper->min_count_label;
+    args.neg = wrapper->neg;
+    args.wordNgrams = wrapper->word_ngrams;
+    args.loss = fasttext::loss_name(wrapper->loss);
+    args.model = fasttext::model_name(wrapper->model);
+    args.bucket = wrapper->bucket;
+    args.minn = wrapper->minn;
+    args.maxn = wrapper->maxn;
+    args.thread = wrapper->thread;
+    args.t = wrapper->t;
+    args.verbose = wrapper->verbose;
+    args.cutoff = wrapper->cutoff;
+    args.retrain = wrapper->retrain != 0;
+    args.qnorm = wrapper->qnorm != 0;
+    args.qout = wrapper->qout != 0;
+    args.dsub = wrapper->dsub;
+
+    return args;
+}
+
+void releaseMapping(struct WrapperFastText* wrapper) {
+    if (wrapper->mapped != nullptr) {
+        munmap(wrapper->mapped, wrapper->mapped_size);
+    }
+
+    wrapper->mapped = nullptr;
+    wrapper->mapped_size = 0;
+}
+
 bool checkModelInitialization(const struct WrapperFastText* wrapper) {
     if (wrapper==nullptr ||
         wrapper->model==nullptr ||
@@ -57,8 +583,8 @@ bool checkModelInitialization(const struct WrapperFastText* wrapper) {
     return true;
 }
 
-bool checkModelFile(std::istream& in) {
-    int32_t magic, version;
+bool checkModelFile(std::istream& in, int32_t& version) {
+    int32_t magic;
 
     in.read((char*)&(magic), sizeof(int32_t));
     if (magic != FASTTEXT_FILEFORMAT_MAGIC_INT32) {
@@ -154,6 +680,16 @@ extern "C" {
         return wrapper->dict->nwords();
     }
 
+    void DICT_WordCounts(const struct WrapperDictionary* wrapper, long* counts) {
+        std::vector<int64_t> word_counts = wrapper->dict->getCounts(fasttext::entry_type::word);
+
+        std::copy(word_counts.begin(), word_counts.end(), counts);
+    }
+
+    long DICT_TokensCount(const struct WrapperDictionary* wrapper) {
+        return wrapper->dict->ntokens();
+    }
+
     void VEC_Release(struct WrapperVector* wrapper) {
         delete wrapper->vector;
 
@@ -187,7 +723,9 @@ extern "C" {
     struct WrapperFastText* NewFastText() {
         WrapperFastText *wrapper = (WrapperFastText *)malloc(sizeof (struct WrapperFastText));
 
-        wrapper->model = new fasttext::FastText();
+        wrapper->model = new FastTextModel();
+        wrapper->mapped = nullptr;
+        wrapper->mapped_size = 0;
 
         return wrapper;
     }
@@ -199,17 +737,85 @@ extern "C" {
             return RES_ERROR_NOT_OPEN;
         }
 
-        if (!checkModelFile(ifs)) {
+        int32_t version;
+        if (!checkModelFile(ifs, version)) {
             return RES_ERROR_WRONG_MODEL;
         }
 
         wrapper->model->loadModel(ifs);
+        wrapper->model->setFileVersion(version);
 
         ifs.close();
 
         return RES_OK;
     }
 
+    int FT_LoadModelReader(struct WrapperFastText* wrapper, void* reader, ReadCallback read) {
+        ReaderStreamBuf buf(reader, read);
+        std::istream in(&buf);
+
+        int32_t version;
+        if (!checkModelFile(in, version)) {
+            return buf.failed() ? RES_ERROR_NOT_OPEN : RES_ERROR_WRONG_MODEL;
+        }
+
+        try {
+            wrapper->model->loadModel(in);
+        } catch(std::exception &e) {
+            return RES_ERROR_WRONG_MODEL;
+        }
+
+        if (buf.failed()) {
+            return RES_ERROR_NOT_OPEN;
+        }
+        if (in.fail()) {
+            return RES_ERROR_WRONG_MODEL;
+        }
+
+        wrapper->model->setFileVersion(version);
+
+        return RES_OK;
+    }
+
+    int FT_LoadModelMapped(struct WrapperFastText* wrapper, const char* path) {
+        const int fd = open(path, O_RDONLY);
+        if (fd < 0) {
+            return RES_ERROR_NOT_OPEN;
+        }
+
+        struct stat st;
+        if (fstat(fd, &st) != 0) {
+            close(fd);
+            return RES_ERROR_NOT_OPEN;
+        }
+
+        const size_t size = size_t(st.st_size);
+        void* data = mmap(nullptr, size, PROT_READ, MAP_SHARED, fd, 0);
+        close(fd);
+
+        if (data == MAP_FAILED) {
+            return RES_ERROR_NOT_OPEN;
+        }
+
+        int res;
+        try {
+            res = wrapper->model->loadMapped((const char*)data, size);
+        } catch(std::exception &e) {
+            res = RES_ERROR_WRONG_MODEL;
+        }
+
+        if (res != RES_OK) {
+            munmap(data, size);
+            return res;
+        }
+
+        releaseMapping(wrapper);
+        wrapper->mapped = data;
+        wrapper->mapped_size = size;
+
+        return RES_OK;
+    }
+
     int FT_LoadVectors(struct WrapperFastText* wrapper, const char* path) {
         std::string vectorsPath(path);
 
@@ -249,6 +855,122 @@ extern "C" {
         return wrap_vector;
     }
 
+    int FT_GetModelInfo(const struct WrapperFastText* wrapper, struct WrapperModelInfo* info) {
+        if (!checkModelInitialization(wrapper)) {
+            return RES_ERROR_NOT_INIT;
+        }
+
+        const fasttext::Args& args = wrapper->model->args();
+        std::shared_ptr<const fasttext::Dictionary> dict = wrapper->model->getDictionary();
+
+        info->version = wrapper->model->fileVersion();
+        info->dim = args.dim;
+        info->ws = args.ws;
+        info->epoch = args.epoch;
+        info->min_count = args.minCount;
+        info->neg = args.neg;
+        info->word_ngrams = args.wordNgrams;
+        info->loss = int(args.loss);
+        info->model = int(args.model);
+        info->bucket = args.bucket;
+        info->minn = args.minn;
+        info->maxn = args.maxn;
+        info->lr_update_rate = args.lrUpdateRate;
+        info->t = args.t;
+        info->nwords = dict->nwords();
+        info->nlabels = dict->nlabels();
+        info->ntokens = dict->ntokens();
+        info->quantized = wrapper->model->isQuantized();
+
+        return RES_OK;
+    }
+
+    int FT_GetDimension(const struct WrapperFastText* wrapper) {
+        if (!checkModelInitialization(wrapper)) {
+            return -1;
+        }
+
+        return wrapper->model->getDimension();
+    }
+
+    int FT_GetWordVectorInto(const struct WrapperFastText* wrapper, const char* word, float* out, int len) {
+        if (!checkModelInitialization(wrapper)) {
+            return RES_ERROR_NOT_INIT;
+        }
+
+        try {
+            withBorrowedVector(out, len, [&](fasttext::Vector& vec) {
+                wrapper->model->getWordVector(vec, word);
+            });
+        } catch(std::exception &e) {
+            return RES_ERROR_EXECUTION;
+        }
+
+        return RES_OK;
+    }
+
+    int FT_GetSentenceVectorInto(const struct WrapperFastText* wrapper, const char* text, float* out, int len) {
+        if (!checkModelInitialization(wrapper)) {
+            return RES_ERROR_NOT_INIT;
+        }
+
+        std::istringstream str(text);
+
+        try {
+            withBorrowedVector(out, len, [&](fasttext::Vector& vec) {
+                wrapper->model->getSentenceVector(str, vec);
+            });
+        } catch(std::exception &e) {
+            return RES_ERROR_EXECUTION;
+        }
+
+        return RES_OK;
+    }
+
+    int FT_EmbedWords(const struct WrapperFastText* wrapper, const char** words, int n, float* out, int threads) {
+        if (!checkModelInitialization(wrapper)) {
+            return RES_ERROR_NOT_INIT;
+        }
+
+        const int dim = wrapper->model->getDimension();
+
+        return forEachRow(n, threads, [&](int begin, int end) {
+            for (int i = begin; i < end; i++) {
+                withBorrowedVector(out + int64_t(i) * dim, dim, [&](fasttext::Vector& vec) {
+                    wrapper->model->getWordVector(vec, words[i]);
+                });
+            }
+        });
+    }
+
+    int FT_EmbedSentences(const struct WrapperFastText* wrapper, const char** texts, int n, float* out, int threads) {
+        if (!checkModelInitialization(wrapper)) {
+            return RES_ERROR_NOT_INIT;
+        }
+
+        const int dim = wrapper->model->getDimension();
+
+        return forEachRow(n, threads, [&](int begin, int end) {
+            for (int i = begin; i < end; i++) {
+                std::istringstream str(texts[i]);
+
+                withBorrowedVector(out + int64_t(i) * dim, dim, [&](fasttext::Vector& vec) {
+                    wrapper->model->getSentenceVector(str, vec);
+                });
+            }
+        });
+    }
+
+    struct WrapperVector* FT_GetSentenceVectorMode(const struct WrapperFastText* wrapper, const char* text, short supervised) {
+        std::istringstream str(text);
+
+        struct WrapperVector* wrap_vector = Vector(wrapper->model->getDimension());
+
+        wrapper->model->sentenceVector(str, *wrap_vector->vector, supervised != 0);
+
+        return wrap_vector;
+    }
+
     const struct WrapperPredictResult* FT_Predict(struct WrapperFastText* wrapper, const char* text, int k) {
         std::istringstream str(text);
         std::vector<std::pair<float, std::string>> prediction;
@@ -271,8 +993,117 @@ extern "C" {
         return result;
     }
 
+    int FT_Train(struct WrapperFastText* wrapper, const struct WrapperArgs* args) {
+        const fasttext::Args train_args = makeArgs(args);
+
+        std::ifstream ifs(train_args.input);
+        if (!ifs.good()) {
+            return RES_ERROR_NOT_OPEN;
+        }
+        ifs.close();
+
+        try {
+            wrapper->model->trainSeeded(train_args, args->seed);
+        } catch(std::exception &e) {
+            return RES_ERROR_EXECUTION;
+        }
+
+        wrapper->model->setFileVersion(FASTTEXT_VERSION);
+
+        return RES_OK;
+    }
+
+    int FT_FineTune(struct WrapperFastText* wrapper, const struct WrapperFastText* base, const struct WrapperArgs* args) {
+        if (!checkModelInitialization(base)) {
+            return RES_ERROR_NOT_INIT;
+        }
+        if (base->model->args().model != fasttext::model_name::sup || base->model->isQuantized()
+            || base->model->args().dim != args->dim || base->model->args().bucket != args->bucket) {
+            return RES_ERROR_WRONG_MODEL;
+        }
+
+        const fasttext::Args train_args = makeArgs(args);
+
+        std::ifstream ifs(train_args.input);
+        if (!ifs.good()) {
+            return RES_ERROR_NOT_OPEN;
+        }
+        ifs.close();
+
+        try {
+            wrapper->model->fineTune(*base->model, train_args, args->seed);
+        } catch(std::exception &e) {
+            return RES_ERROR_EXECUTION;
+        }
+
+        wrapper->model->setFileVersion(FASTTEXT_VERSION);
+
+        return RES_OK;
+    }
+
+    int FT_Quantize(struct WrapperFastText* wrapper, const struct WrapperArgs* args) {
+        if (!checkModelInitialization(wrapper)) {
+            return RES_ERROR_NOT_INIT;
+        }
+        if (wrapper->model->args().model != fasttext::model_name::sup) {
+            return RES_ERROR_WRONG_MODEL;
+        }
+
+        const fasttext::Args qargs = makeArgs(args);
+
+        // retraining updates the matrices in place, the ones of a mapped model are read-only
+        if (qargs.retrain && wrapper->mapped != nullptr) {
+            return RES_ERROR_WRONG_MODEL;
+        }
+
+        if (qargs.retrain) {
+            std::ifstream ifs(qargs.input);
+            if (!ifs.good()) {
+                return RES_ERROR_NOT_OPEN;
+            }
+        }
+
+        try {
+            wrapper->model->quantize(qargs);
+        } catch(std::exception &e) {
+            return RES_ERROR_EXECUTION;
+        }
+
+        return RES_OK;
+    }
+
+    int FT_SaveModel(const struct WrapperFastText* wrapper, const char* path) {
+        if (!checkModelInitialization(wrapper)) {
+            return RES_ERROR_NOT_INIT;
+        }
+
+        std::ofstream ofs(path, std::ofstream::binary);
+        if (!ofs.is_open()) {
+            return RES_ERROR_NOT_OPEN;
+        }
+
+        wrapper->model->saveModelTo(ofs);
+        ofs.close();
+
+        return ofs.fail() ? RES_ERROR_NOT_OPEN : RES_OK;
+    }
+
+    void FT_GetNgramVectors(const struct WrapperFastText* wrapper, const char* word, void* ctx, NgramCallback callback) {
+        std::vector<int32_t> ngrams;
+        std::vector<std::string> substrings;
+        fasttext::Vector vec(wrapper->model->getDimension());
+
+        wrapper->model->ngrams(word, ngrams, substrings);
+
+        for (size_t i = 0; i < ngrams.size() && i < substrings.size(); i++) {
+            wrapper->model->inputRow(vec, ngrams[i]);
+            callback(ctx, substrings[i].c_str(), vec.data_, int(vec.size()));
+        }
+    }
+
     void FT_Release(struct WrapperFastText* wrapper) {
         delete wrapper->model;
+        releaseMapping(wrapper);
 
         free(wrapper);
     }
@@ -0,0 +1,124 @@
+use std::io::Read;
+
+use super::binary::{read_f64, read_i32};
+use super::Error;
+
+/// The kind of a model, `model_name` in fastText.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ModelKind {
+    Cbow,
+    Skipgram,
+    Supervised,
+}
+
+impl ModelKind {
+    pub(crate) fn from_i32(value: i32) -> Result<ModelKind, Error> {
+        match value {
+            1 => Ok(ModelKind::Cbow),
+            2 => Ok(ModelKind::Skipgram),
+            3 => Ok(ModelKind::Supervised),
+            _ => Err(Error::Corrupted(format!("unknown model kind {}", value))),
+        }
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
+    pub(crate) fn to_i32(&self) -> i32 {
+        match *self {
+            ModelKind::Cbow => 1,
+            ModelKind::Skipgram => 2,
+            ModelKind::Supervised => 3,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ModelKind::Cbow => "cbow",
+            ModelKind::Skipgram => "sg",
+            ModelKind::Supervised => "sup",
+        }
+    }
+}
+
+/// The loss function of a model, `loss_name` in fastText.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Loss {
+    HierarchicalSoftmax,
+    NegativeSampling,
+    Softmax,
+    OneVsAll,
+}
+
+impl Loss {
+    pub(crate) fn from_i32(value: i32) -> Result<Loss, Error> {
+        match value {
+            1 => Ok(Loss::HierarchicalSoftmax),
+            2 => Ok(Loss::NegativeSampling),
+            3 => Ok(Loss::Softmax),
+            4 => Ok(Loss::OneVsAll),
+            _ => Err(Error::Corrupted(format!("unknown loss {}", value))),
+        }
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
+    pub(crate) fn to_i32(&self) -> i32 {
+        match *self {
+            Loss::HierarchicalSoftmax => 1,
+            Loss::NegativeSampling => 2,
+            Loss::Softmax => 3,
+            Loss::OneVsAll => 4,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Loss::HierarchicalSoftmax => "hs",
+            Loss::NegativeSampling => "ns",
+            Loss::Softmax => "softmax",
+            Loss::OneVsAll => "one-vs-all",
+        }
+    }
+}
+
+/// The training arguments stored in a model file.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Args {
+    pub dim: i32,
+    pub ws: i32,
+    pub epoch: i32,
+    pub min_count: i32,
+    pub neg: i32,
+    pub word_ngrams: i32,
+    pub loss: Loss,
+    pub model: ModelKind,
+    pub bucket: i32,
+    pub minn: i32,
+    pub maxn: i32,
+    pub lr_update_rate: i32,
+    pub t: f64,
+}
+
+impl Args {
+    /// The size of the args block in a model file.
+    pub const BLOCK_LEN: u64 = 12 * 4 + 8;
+
+    pub(crate) fn read<R: Read>(reader: &mut R) -> Result<Args, Error> {
+        Ok(Args {
+            dim: read_i32(reader)?,
+            ws: read_i32(reader)?,
+            epoch: read_i32(reader)?,
+            min_count: read_i32(reader)?,
+            neg: read_i32(reader)?,
+            word_ngrams: read_i32(reader)?,
+            loss: Loss::from_i32(read_i32(reader)?)?,
+            model: ModelKind::from_i32(read_i32(reader)?)?,
+            bucket: read_i32(reader)?,
+            minn: read_i32(reader)?,
+            maxn: read_i32(reader)?,
+            lr_update_rate: read_i32(reader)?,
+            t: read_f64(reader)?,
+        })
+    }
+}
@@ -0,0 +1,73 @@
+use std::io::{self, Read};
+
+const CHUNK_LEN: usize = 64 * 1024;
+
+pub(crate) fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut buf = [0u8; 1];
+    reader.read_exact(&mut buf)?;
+
+    Ok(buf[0])
+}
+
+pub(crate) fn read_bool<R: Read>(reader: &mut R) -> io::Result<bool> {
+    Ok(read_u8(reader)? != 0)
+}
+
+pub(crate) fn read_i32<R: Read>(reader: &mut R) -> io::Result<i32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+
+    Ok(i32::from_le_bytes(buf))
+}
+
+pub(crate) fn read_i64<R: Read>(reader: &mut R) -> io::Result<i64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+
+    Ok(i64::from_le_bytes(buf))
+}
+
+pub(crate) fn read_f64<R: Read>(reader: &mut R) -> io::Result<f64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+
+    Ok(f64::from_le_bytes(buf))
+}
+
+/// Reads a NUL-terminated string as the C++ `Dictionary::load` does.
+pub(crate) fn read_cstring<R: Read>(reader: &mut R) -> io::Result<String> {
+    let mut buf = Vec::new();
+
+    loop {
+        match read_u8(reader)? {
+            0 => break,
+            c => buf.push(c),
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// Reads `len` bytes growing the buffer chunk by chunk, so that a corrupted length fails
+/// with `UnexpectedEof` instead of a huge up-front allocation.
+pub(crate) fn read_bytes<R: Read>(reader: &mut R, len: usize) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(len.min(CHUNK_LEN));
+
+    while buf.len() < len {
+        let start = buf.len();
+        let end = len.min(start + CHUNK_LEN);
+
+        buf.resize(end, 0u8);
+        reader.read_exact(&mut buf[start..end])?;
+    }
+
+    Ok(buf)
+}
+
+pub(crate) fn read_f32_vec<R: Read>(reader: &mut R, len: usize) -> io::Result<Vec<f32>> {
+    let bytes = read_bytes(reader, len * 4)?;
+
+    Ok(bytes.chunks(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
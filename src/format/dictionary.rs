@@ -0,0 +1,91 @@
+use std::io::Read;
+
+use super::binary::{read_cstring, read_i32, read_i64, read_u8};
+use super::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryType {
+    Word,
+    Label,
+}
+
+/// A dictionary entry: a word or a label with its count in the training data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub word: String,
+    pub count: i64,
+    pub entry_type: EntryType,
+}
+
+/// The dictionary section of a model file. Words come first, then labels.
+#[derive(Debug, Clone)]
+pub struct Dictionary {
+    pub nwords: i32,
+    pub nlabels: i32,
+    pub ntokens: i64,
+    pub entries: Vec<Entry>,
+    /// `-1` when the model was not pruned by quantization.
+    pub pruneidx_size: i64,
+    pub pruneidx: Vec<(i32, i32)>,
+}
+
+impl Dictionary {
+    pub(crate) fn read<R: Read>(reader: &mut R) -> Result<Dictionary, Error> {
+        let size = read_i32(reader)?;
+        let nwords = read_i32(reader)?;
+        let nlabels = read_i32(reader)?;
+        let ntokens = read_i64(reader)?;
+        let pruneidx_size = read_i64(reader)?;
+
+        if size < 0 || nwords < 0 || nlabels < 0 || nwords as i64 + nlabels as i64 != size as i64 {
+            return Err(Error::Corrupted(format!("wrong dictionary size {} for {} words and {} labels", size, nwords, nlabels)));
+        }
+
+        // `size` comes from the file, the entries are only trusted once read
+        let mut entries = Vec::new();
+        for _ in 0..size {
+            let word = read_cstring(reader)?;
+            let count = read_i64(reader)?;
+            let entry_type = match read_u8(reader)? {
+                0 => EntryType::Word,
+                1 => EntryType::Label,
+                t => return Err(Error::Corrupted(format!("unknown type {} of dictionary entry {:?}", t, word))),
+            };
+
+            entries.push(Entry {
+                word,
+                count,
+                entry_type,
+            });
+        }
+
+        let mut pruneidx = Vec::new();
+        for _ in 0..pruneidx_size.max(0) {
+            let first = read_i32(reader)?;
+            let second = read_i32(reader)?;
+
+            pruneidx.push((first, second));
+        }
+
+        Ok(Dictionary {
+            nwords,
+            nlabels,
+            ntokens,
+            entries,
+            pruneidx_size,
+            pruneidx,
+        })
+    }
+
+    pub fn words(&self) -> &[Entry] {
+        &self.entries[..self.nwords as usize]
+    }
+
+    pub fn labels(&self) -> &[Entry] {
+        &self.entries[self.nwords as usize..]
+    }
+
+    pub fn is_pruned(&self) -> bool {
+        self.pruneidx_size >= 0
+    }
+}
@@ -0,0 +1,163 @@
+//! Text dumps of a model, as `fasttext dump` writes them.
+
+use std::io::{self, Write};
+
+use super::{EntryType, Matrix, Model};
+
+/// Formats a float as `std::ostream` does with `std::setprecision(precision)`, i.e. `%.{precision}g`.
+pub fn format_float(value: f64, precision: usize) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return format!("{}", if value == 0.0 { 0.0 } else { value });
+    }
+
+    let precision = precision.max(1);
+    let sci = format!("{:.*e}", precision - 1, value);
+    let (mantissa, exp) = sci.split_at(sci.find('e').unwrap_or(sci.len()));
+    let exp: i32 = exp[1..].parse().unwrap_or(0);
+
+    if exp < -4 || exp >= precision as i32 {
+        format!("{}e{}{:02}", trim_zeros(mantissa), if exp < 0 { '-' } else { '+' }, exp.abs())
+    } else {
+        trim_zeros(&format!("{:.*}", (precision as i32 - 1 - exp) as usize, value))
+    }
+}
+
+fn trim_zeros(number: &str) -> String {
+    let mut number = number.to_owned();
+
+    if number.contains('.') {
+        while number.ends_with('0') {
+            number.pop();
+        }
+        if number.ends_with('.') {
+            number.pop();
+        }
+    }
+
+    number
+}
+
+fn dump_matrix<W: Write>(matrix: &Matrix, mut writer: W) -> io::Result<()> {
+    let matrix = match *matrix {
+        Matrix::Dense(ref matrix) => matrix,
+        Matrix::Quantized(_) => return Err(io::Error::new(io::ErrorKind::InvalidInput, "Not supported for quantized models.")),
+    };
+
+    writeln!(writer, "{} {}", matrix.rows, matrix.cols)?;
+    for i in 0..matrix.rows {
+        let values: Vec<String> = matrix.row(i).iter().map(|v| format_float(*v as f64, 5)).collect();
+        writeln!(writer, "{}", values.join(" "))?;
+    }
+
+    writer.flush()
+}
+
+impl Model {
+    /// Writes the training arguments, one `name value` per line, `dump args`.
+    pub fn dump_args<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let args = &self.args;
+
+        writeln!(writer, "dim {}", args.dim)?;
+        writeln!(writer, "ws {}", args.ws)?;
+        writeln!(writer, "epoch {}", args.epoch)?;
+        writeln!(writer, "minCount {}", args.min_count)?;
+        writeln!(writer, "neg {}", args.neg)?;
+        writeln!(writer, "wordNgrams {}", args.word_ngrams)?;
+        writeln!(writer, "loss {}", args.loss.name())?;
+        writeln!(writer, "model {}", args.model.name())?;
+        writeln!(writer, "bucket {}", args.bucket)?;
+        writeln!(writer, "minn {}", args.minn)?;
+        writeln!(writer, "maxn {}", args.maxn)?;
+        writeln!(writer, "lrUpdateRate {}", args.lr_update_rate)?;
+        writeln!(writer, "t {}", format_float(args.t, 6))?;
+
+        writer.flush()
+    }
+
+    /// Writes the number of entries, then `word count type` for each of them, `dump dict`.
+    pub fn dump_dictionary<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let entries = &self.dictionary.entries;
+
+        writeln!(writer, "{}", entries.len())?;
+        for entry in entries {
+            let entry_type = match entry.entry_type {
+                EntryType::Word => "word",
+                EntryType::Label => "label",
+            };
+            writeln!(writer, "{} {} {}", entry.word, entry.count, entry_type)?;
+        }
+
+        writer.flush()
+    }
+
+    /// Writes the shape then the rows of the input matrix, `dump input`.
+    ///
+    /// Quantized matrices are not supported, as in fastText.
+    pub fn dump_input<W: Write>(&self, writer: W) -> io::Result<()> {
+        dump_matrix(&self.input, writer)
+    }
+
+    /// Writes the shape then the rows of the output matrix, `dump output`.
+    pub fn dump_output<W: Write>(&self, writer: W) -> io::Result<()> {
+        dump_matrix(&self.output, writer)
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use format::{Args, DenseMatrix, Dictionary, Entry, Loss, ModelKind};
+
+    fn model() -> Model {
+        let entry = |word: &str, count, entry_type| Entry { word: word.to_owned(), count, entry_type };
+
+        Model {
+            version: 12,
+            args: Args {
+                dim: 2, ws: 5, epoch: 5, min_count: 1, neg: 5, word_ngrams: 1,
+                loss: Loss::Softmax, model: ModelKind::Supervised,
+                bucket: 0, minn: 0, maxn: 0, lr_update_rate: 100, t: 0.0001,
+            },
+            dictionary: Dictionary {
+                nwords: 1,
+                nlabels: 1,
+                ntokens: 4,
+                entries: vec![entry("hello", 3, EntryType::Word), entry("__label__a", 1, EntryType::Label)],
+                pruneidx_size: -1,
+                pruneidx: Vec::new(),
+            },
+            input: Matrix::Dense(DenseMatrix { rows: 1, cols: 2, data: vec![0.5, -0.125] }),
+            qout: false,
+            output: Matrix::Dense(DenseMatrix { rows: 1, cols: 2, data: vec![1.0, 0.0] }),
+        }
+    }
+
+    #[test]
+    fn test_dump_model() {
+        let model = model();
+
+        let mut dict = Vec::new();
+        model.dump_dictionary(&mut dict).unwrap();
+        assert_eq!(String::from_utf8(dict).unwrap(), "2\nhello 3 word\n__label__a 1 label\n");
+
+        let mut input = Vec::new();
+        model.dump_input(&mut input).unwrap();
+        assert_eq!(String::from_utf8(input).unwrap(), "1 2\n0.5 -0.125\n");
+
+        let mut args = Vec::new();
+        model.dump_args(&mut args).unwrap();
+        let args = String::from_utf8(args).unwrap();
+        assert!(args.contains("loss softmax\nmodel sup\n"), "check loss and model in {}", args);
+        assert!(args.ends_with("t 0.0001\n"), "check t in {}", args);
+    }
+
+    #[test]
+    fn test_format_float() {
+        assert_eq!(format_float(0.0001, 6), "0.0001");
+        assert_eq!(format_float(0.1234567, 6), "0.123457");
+        assert_eq!(format_float(-2.5, 5), "-2.5");
+        assert_eq!(format_float(1234567.0, 6), "1.23457e+06");
+        assert_eq!(format_float(0.00001234, 5), "1.234e-05");
+        assert_eq!(format_float(0.0, 5), "0");
+    }
+}
@@ -0,0 +1,397 @@
+//! Validation of model files from their headers and section sizes, without reading the matrices.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use result::Err;
+
+use super::binary::{read_bool, read_i32};
+use super::matrix::{read_shape, KSUB};
+use super::{read_header, Args, Dictionary, Error, ModelKind};
+
+/// The sections of a model file, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionKind {
+    Header,
+    Args,
+    Dictionary,
+    InputMatrix,
+    OutputMatrix,
+}
+
+/// A section of a model file found at `offset` and spanning `len` bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub kind: SectionKind,
+    pub offset: u64,
+    pub len: u64,
+    /// The rows and columns of a matrix section.
+    pub shape: Option<(usize, usize)>,
+    pub quantized: bool,
+}
+
+/// The first problem found in a model file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    WrongMagic(i32),
+    UnsupportedVersion(i32),
+    /// The file ends inside the section starting at `offset`, which declares that it ends at `end` when known.
+    Truncated { section: SectionKind, offset: u64, end: Option<u64> },
+    Corrupted { section: SectionKind, offset: u64, msg: String },
+    /// Bytes after the output matrix.
+    TrailingBytes { offset: u64, len: u64 },
+}
+
+/// What `inspect_file` found out about a model file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileReport {
+    pub file_len: u64,
+    pub version: Option<i32>,
+    pub args: Option<Args>,
+    pub nwords: Option<i32>,
+    pub nlabels: Option<i32>,
+    /// The sections read successfully.
+    pub sections: Vec<Section>,
+    pub issue: Option<Issue>,
+}
+
+impl FileReport {
+    pub fn is_valid(&self) -> bool {
+        self.issue.is_none()
+    }
+}
+
+enum Stop {
+    Issue(Issue),
+    Io(io::Error),
+}
+
+impl From<io::Error> for Stop {
+    fn from(err: io::Error) -> Stop {
+        Stop::Io(err)
+    }
+}
+
+struct Inspector<R> {
+    reader: R,
+    file_len: u64,
+    section: SectionKind,
+    offset: u64,
+}
+
+impl<R: Read + Seek> Inspector<R> {
+    fn position(&mut self) -> io::Result<u64> {
+        self.reader.seek(SeekFrom::Current(0))
+    }
+
+    fn begin(&mut self, section: SectionKind) -> io::Result<()> {
+        self.section = section;
+        self.offset = self.position()?;
+
+        Ok(())
+    }
+
+    fn end(&mut self, report: &mut FileReport, shape: Option<(usize, usize)>, quantized: bool) -> io::Result<()> {
+        let end = self.position()?;
+
+        report.sections.push(Section {
+            kind: self.section,
+            offset: self.offset,
+            len: end - self.offset,
+            shape,
+            quantized,
+        });
+
+        Ok(())
+    }
+
+    fn corrupted(&self, msg: String) -> Stop {
+        Stop::Issue(Issue::Corrupted { section: self.section, offset: self.offset, msg })
+    }
+
+    /// Maps an error of the format reader to the issue of the current section.
+    fn error(&self, err: Error) -> Stop {
+        match err {
+            Error::Io(ref err) if err.kind() == io::ErrorKind::UnexpectedEof =>
+                Stop::Issue(Issue::Truncated { section: self.section, offset: self.offset, end: None }),
+            Error::Io(err) => Stop::Io(err),
+            Error::WrongMagic(magic) => Stop::Issue(Issue::WrongMagic(magic)),
+            Error::UnsupportedVersion(version) => Stop::Issue(Issue::UnsupportedVersion(version)),
+            Error::Corrupted(msg) => self.corrupted(msg),
+        }
+    }
+
+    fn read<T, F>(&mut self, f: F) -> Result<T, Stop>
+        where F: FnOnce(&mut R) -> Result<T, Error>
+    {
+        match f(&mut self.reader) {
+            Ok(value) => Ok(value),
+            Err(err) => Err(self.error(err)),
+        }
+    }
+
+    /// Skips `len` bytes of the current section, checking that the file holds them.
+    fn skip(&mut self, len: u64) -> Result<(), Stop> {
+        let end = self.position()?.checked_add(len);
+
+        match end {
+            Some(end) if end <= self.file_len => {
+                self.reader.seek(SeekFrom::Start(end))?;
+                Ok(())
+            }
+            _ => Err(Stop::Issue(Issue::Truncated { section: self.section, offset: self.offset, end })),
+        }
+    }
+
+    fn skip_dense(&mut self) -> Result<(usize, usize), Stop> {
+        let (rows, cols) = self.read(read_shape)?;
+        self.skip(rows as u64 * cols as u64 * 4)?;
+
+        Ok((rows, cols))
+    }
+
+    fn skip_product_quantizer(&mut self) -> Result<i32, Stop> {
+        let (dim, nsubq) = self.read(|reader| {
+            let dim = read_i32(reader)?;
+            let nsubq = read_i32(reader)?;
+            let dsub = read_i32(reader)?;
+            let lastdsub = read_i32(reader)?;
+
+            if dim < 0 || nsubq <= 0 || dsub <= 0 || lastdsub <= 0 || lastdsub > dsub {
+                return Err(Error::Corrupted(format!("wrong product quantizer shape dim {} nsubq {} dsub {} lastdsub {}", dim, nsubq, dsub, lastdsub)));
+            }
+
+            Ok((dim, nsubq))
+        })?;
+
+        self.skip(dim as u64 * KSUB as u64 * 4)?;
+
+        Ok(nsubq)
+    }
+
+    fn skip_quantized(&mut self) -> Result<(usize, usize), Stop> {
+        let (qnorm, (rows, cols), codesize) = self.read(|reader| {
+            let qnorm = read_bool(reader)?;
+            let shape = read_shape(reader)?;
+            let codesize = read_i32(reader)?;
+
+            Ok((qnorm, shape, codesize))
+        })?;
+
+        if codesize < 0 {
+            return Err(self.corrupted(format!("negative code size {}", codesize)));
+        }
+        self.skip(codesize as u64)?;
+
+        let nsubq = self.skip_product_quantizer()?;
+        if codesize as u64 != rows as u64 * nsubq as u64 {
+            return Err(self.corrupted(format!("wrong code size {} for {} rows of {} subquantizers", codesize, rows, nsubq)));
+        }
+
+        if qnorm {
+            self.skip(rows as u64)?;
+            self.skip_product_quantizer()?;
+        }
+
+        Ok((rows, cols))
+    }
+
+    fn skip_matrix(&mut self, quantized: bool) -> Result<(usize, usize), Stop> {
+        if quantized {
+            self.skip_quantized()
+        } else {
+            self.skip_dense()
+        }
+    }
+
+    fn inspect(&mut self, report: &mut FileReport) -> Result<(), Stop> {
+        self.begin(SectionKind::Header)?;
+        let version = self.read(read_header)?;
+        report.version = Some(version);
+        self.end(report, None, false)?;
+
+        self.begin(SectionKind::Args)?;
+        let mut args = self.read(Args::read)?;
+        if version == 11 && args.model == ModelKind::Supervised {
+            // as `Model::read`, old supervised models do not use char ngrams
+            args.maxn = 0;
+        }
+        report.args = Some(args.clone());
+        self.end(report, None, false)?;
+
+        self.begin(SectionKind::Dictionary)?;
+        let dict = self.read(Dictionary::read)?;
+        report.nwords = Some(dict.nwords);
+        report.nlabels = Some(dict.nlabels);
+        self.end(report, None, false)?;
+
+        self.begin(SectionKind::InputMatrix)?;
+        let quant_input = self.read(|reader| Ok(read_bool(reader)?))?;
+        let (rows, cols) = self.skip_matrix(quant_input)?;
+        if cols != args.dim as usize {
+            return Err(self.corrupted(format!("{} columns for dimension {}", cols, args.dim)));
+        }
+        if !quant_input && rows as i64 != dict.nwords as i64 + args.bucket as i64 {
+            return Err(self.corrupted(format!("{} rows for {} words and {} buckets", rows, dict.nwords, args.bucket)));
+        }
+        if !quant_input && dict.is_pruned() {
+            return Err(self.corrupted(String::from("a pruned dictionary requires a quantized input matrix")));
+        }
+        self.end(report, Some((rows, cols)), quant_input)?;
+
+        self.begin(SectionKind::OutputMatrix)?;
+        let qout = self.read(|reader| Ok(read_bool(reader)?))?;
+        let quant_output = quant_input && qout;
+        let (rows, cols) = self.skip_matrix(quant_output)?;
+        let targets = if args.model == ModelKind::Supervised { dict.nlabels } else { dict.nwords };
+        if cols != args.dim as usize {
+            return Err(self.corrupted(format!("{} columns for dimension {}", cols, args.dim)));
+        }
+        if rows as i64 != targets as i64 {
+            return Err(self.corrupted(format!("{} rows for {} targets", rows, targets)));
+        }
+        self.end(report, Some((rows, cols)), quant_output)?;
+
+        let end = self.position()?;
+        if end < self.file_len {
+            return Err(Stop::Issue(Issue::TrailingBytes { offset: end, len: self.file_len - end }));
+        }
+
+        Ok(())
+    }
+}
+
+/// Inspects a model file as `checkModelFile` and `loadModel` would read it, seeking over the
+/// matrices instead of loading them.
+///
+/// Only an unreadable file is an error; problems of its content are reported in `FileReport::issue`.
+pub fn inspect_file(path: &Path) -> Result<FileReport, Err> {
+    let file = File::open(path).map_err(|_| Err::ResErrorNotOpen)?;
+    let file_len = file.metadata().map_err(|_| Err::ResErrorNotOpen)?.len();
+
+    inspect(BufReader::new(file), file_len)
+}
+
+pub(crate) fn inspect<R: Read + Seek>(reader: R, file_len: u64) -> Result<FileReport, Err> {
+    let mut report = FileReport {
+        file_len,
+        ..FileReport::default()
+    };
+
+    let mut inspector = Inspector {
+        reader,
+        file_len,
+        section: SectionKind::Header,
+        offset: 0,
+    };
+
+    match inspector.inspect(&mut report) {
+        Ok(()) => Ok(report),
+        Err(Stop::Issue(issue)) => {
+            report.issue = Some(issue);
+            Ok(report)
+        }
+        Err(Stop::Io(_)) => Err(Err::ResErrorNotOpen),
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use std::io::Cursor;
+
+    use format::VERSION;
+    use testing::fixtures::model_bytes;
+
+    /// A skipgram model of dimension 2 with the words "a" and "b" and one bucket.
+    fn tiny_model() -> Vec<u8> {
+        model_bytes(&[("a", 5), ("b", 5)], 3, &[&[0.0, 0.0][..]; 3])
+    }
+
+    fn inspect_bytes(buf: Vec<u8>) -> FileReport {
+        let len = buf.len() as u64;
+        inspect(Cursor::new(buf), len).unwrap()
+    }
+
+    #[test]
+    fn test_inspect_valid() {
+        let report = inspect_bytes(tiny_model());
+
+        assert_eq!(report.issue, None);
+        assert_eq!(report.version, Some(VERSION));
+        assert_eq!(report.nwords, Some(2));
+        assert_eq!(report.sections.len(), 5);
+        assert_eq!(report.sections[3].shape, Some((3, 2)));
+        assert_eq!(report.sections[4].offset + report.sections[4].len, report.file_len);
+    }
+
+    #[test]
+    fn test_inspect_truncated() {
+        let mut buf = tiny_model();
+        let len = buf.len();
+        buf.truncate(len - 4);
+
+        let report = inspect_bytes(buf);
+        let output = len as u64 - 1 - 16 - 2 * 2 * 4;
+        assert_eq!(report.issue, Some(Issue::Truncated { section: SectionKind::OutputMatrix, offset: output, end: Some(len as u64) }));
+        assert_eq!(report.sections.len(), 4);
+
+        let report = inspect_bytes(tiny_model()[..10].to_owned());
+        assert_eq!(report.issue, Some(Issue::Truncated { section: SectionKind::Args, offset: 8, end: None }));
+    }
+
+    #[test]
+    fn test_inspect_corrupted() {
+        let mut buf = tiny_model();
+        buf.push(0);
+        match inspect_bytes(buf).issue {
+            Some(Issue::TrailingBytes { len: 1, .. }) => assert!(true),
+            issue => assert!(false, "trailing byte wasn't reported: {:?}", issue),
+        }
+
+        let mut buf = tiny_model();
+        buf[0] = 0;
+        match inspect_bytes(buf).issue {
+            Some(Issue::WrongMagic(_)) => assert!(true),
+            issue => assert!(false, "wrong magic wasn't reported: {:?}", issue),
+        }
+
+        let mut buf = tiny_model();
+        let dim_offset = 8;
+        buf[dim_offset] = 3;
+        match inspect_bytes(buf).issue {
+            Some(Issue::Corrupted { section: SectionKind::InputMatrix, .. }) => assert!(true),
+            issue => assert!(false, "wrong dimension wasn't reported: {:?}", issue),
+        }
+    }
+
+    #[test]
+    fn test_inspect_huge_dictionary() {
+        let mut buf = tiny_model();
+        let size_offset = 8 + 12 * 4 + 8;
+        buf[size_offset..size_offset + 4].copy_from_slice(&i32::max_value().to_le_bytes());
+        buf[size_offset + 4..size_offset + 8].copy_from_slice(&i32::max_value().to_le_bytes());
+        // the file ends after the two entries it holds
+        buf.truncate(size_offset + 28 + 2 * 11);
+
+        let report = inspect_bytes(buf);
+        assert_eq!(report.issue, Some(Issue::Truncated { section: SectionKind::Dictionary, offset: size_offset as u64, end: None }));
+        assert_eq!(report.sections.len(), 2);
+    }
+
+    #[test]
+    fn test_inspect_old_supervised() {
+        let mut buf = tiny_model();
+        buf[4..8].copy_from_slice(&11i32.to_le_bytes());
+        let model_offset = 8 + 7 * 4;
+        buf[model_offset..model_offset + 4].copy_from_slice(&3i32.to_le_bytes());
+
+        let report = inspect_bytes(buf);
+        assert_eq!(report.version, Some(11));
+        match report.args {
+            Some(ref args) => assert_eq!(args.maxn, 0),
+            None => assert!(false, "args weren't read: {:?}", report.issue),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::io::Read;
+
+use super::binary::{read_bool, read_bytes, read_f32_vec, read_i32, read_i64};
+use super::Error;
+
+/// The number of centroids of each product quantizer subspace.
+pub const KSUB: usize = 256;
+
+/// A dense row-major matrix, `Matrix` in fastText.
+#[derive(Debug, Clone)]
+pub struct DenseMatrix {
+    pub rows: usize,
+    pub cols: usize,
+    pub data: Vec<f32>,
+}
+
+impl DenseMatrix {
+    pub(crate) fn read<R: Read>(reader: &mut R) -> Result<DenseMatrix, Error> {
+        let (rows, cols) = read_shape(reader)?;
+        let data = read_f32_vec(reader, rows * cols)?;
+
+        Ok(DenseMatrix {
+            rows,
+            cols,
+            data,
+        })
+    }
+
+    pub fn row(&self, i: usize) -> &[f32] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+}
+
+/// A product quantizer, `ProductQuantizer` in fastText.
+#[derive(Debug, Clone)]
+pub struct ProductQuantizer {
+    pub dim: i32,
+    pub nsubq: i32,
+    pub dsub: i32,
+    pub lastdsub: i32,
+    pub centroids: Vec<f32>,
+}
+
+impl ProductQuantizer {
+    pub(crate) fn read<R: Read>(reader: &mut R) -> Result<ProductQuantizer, Error> {
+        let dim = read_i32(reader)?;
+        let nsubq = read_i32(reader)?;
+        let dsub = read_i32(reader)?;
+        let lastdsub = read_i32(reader)?;
+
+        if dim < 0 || nsubq <= 0 || dsub <= 0 || lastdsub <= 0 || lastdsub > dsub {
+            return Err(Error::Corrupted(format!("wrong product quantizer shape dim {} nsubq {} dsub {} lastdsub {}", dim, nsubq, dsub, lastdsub)));
+        }
+
+        let centroids = read_f32_vec(reader, dim as usize * KSUB)?;
+
+        Ok(ProductQuantizer {
+            dim,
+            nsubq,
+            dsub,
+            lastdsub,
+            centroids,
+        })
+    }
+
+    /// The centroid `i` of the subspace `m`, as `get_centroids` in fastText.
+    pub fn centroid(&self, m: usize, i: u8) -> &[f32] {
+        let dsub = self.dsub as usize;
+        let i = i as usize;
+
+        if m == self.nsubq as usize - 1 {
+            let lastdsub = self.lastdsub as usize;
+            let start = m * KSUB * dsub + i * lastdsub;
+            &self.centroids[start..start + lastdsub]
+        } else {
+            let start = (m * KSUB + i) * dsub;
+            &self.centroids[start..start + dsub]
+        }
+    }
+}
+
+/// A quantized matrix, `QMatrix` in fastText.
+#[derive(Debug, Clone)]
+pub struct QuantizedMatrix {
+    pub qnorm: bool,
+    pub rows: usize,
+    pub cols: usize,
+    pub codes: Vec<u8>,
+    pub pq: ProductQuantizer,
+    pub norm_codes: Vec<u8>,
+    pub npq: Option<ProductQuantizer>,
+}
+
+impl QuantizedMatrix {
+    pub(crate) fn read<R: Read>(reader: &mut R) -> Result<QuantizedMatrix, Error> {
+        let qnorm = read_bool(reader)?;
+        let (rows, cols) = read_shape(reader)?;
+
+        let codesize = read_i32(reader)?;
+        if codesize < 0 {
+            return Err(Error::Corrupted(format!("negative code size {}", codesize)));
+        }
+
+        let codes = read_bytes(reader, codesize as usize)?;
+        let pq = ProductQuantizer::read(reader)?;
+
+        if codes.len() != rows * pq.nsubq as usize {
+            return Err(Error::Corrupted(format!("wrong code size {} for {} rows of {} subquantizers", codes.len(), rows, pq.nsubq)));
+        }
+
+        let (norm_codes, npq) = if qnorm {
+            (read_bytes(reader, rows)?, Some(ProductQuantizer::read(reader)?))
+        } else {
+            (vec![], None)
+        };
+
+        Ok(QuantizedMatrix {
+            qnorm,
+            rows,
+            cols,
+            codes,
+            pq,
+            norm_codes,
+            npq,
+        })
+    }
+}
+
+/// An input or output matrix of a model.
+#[derive(Debug, Clone)]
+pub enum Matrix {
+    Dense(DenseMatrix),
+    Quantized(QuantizedMatrix),
+}
+
+impl Matrix {
+    pub fn rows(&self) -> usize {
+        match *self {
+            Matrix::Dense(ref m) => m.rows,
+            Matrix::Quantized(ref m) => m.rows,
+        }
+    }
+
+    pub fn cols(&self) -> usize {
+        match *self {
+            Matrix::Dense(ref m) => m.cols,
+            Matrix::Quantized(ref m) => m.cols,
+        }
+    }
+
+    pub fn is_quantized(&self) -> bool {
+        match *self {
+            Matrix::Dense(_) => false,
+            Matrix::Quantized(_) => true,
+        }
+    }
+}
+
+pub(crate) fn read_shape<R: Read>(reader: &mut R) -> Result<(usize, usize), Error> {
+    let rows = read_i64(reader)?;
+    let cols = read_i64(reader)?;
+
+    if rows < 0 || cols < 0 || (rows as u64).checked_mul(cols as u64).and_then(|n| n.checked_mul(4)).is_none() {
+        return Err(Error::Corrupted(format!("wrong matrix shape {}x{}", rows, cols)));
+    }
+
+    Ok((rows as usize, cols as usize))
+}
@@ -0,0 +1,167 @@
+//! A pure-Rust reader of the fastText binary model format (`.bin` and quantized `.ftz`).
+//! The text format of `.vec` files is handled by the `vec` module.
+//!
+//! It mirrors `FastText::loadModel` without linking the C++ library, so models can be
+//! inspected and validated by tooling that only has Rust available.
+
+mod args;
+pub(crate) mod binary;
+mod dictionary;
+pub mod dump;
+pub mod inspect;
+mod matrix;
+pub mod vec;
+
+use std::error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+use self::binary::{read_bool, read_i32};
+
+pub use self::args::{Args, Loss, ModelKind};
+pub use self::dictionary::{Dictionary, Entry, EntryType};
+pub use self::matrix::{DenseMatrix, Matrix, ProductQuantizer, QuantizedMatrix, KSUB};
+
+/// `FASTTEXT_FILEFORMAT_MAGIC_INT32`
+pub const FILEFORMAT_MAGIC: i32 = 793712314;
+/// `FASTTEXT_VERSION`, the latest supported file format version.
+pub const VERSION: i32 = 12;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    WrongMagic(i32),
+    UnsupportedVersion(i32),
+    Corrupted(String),
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref err) => write!(f, "{}", err),
+            Error::WrongMagic(magic) => write!(f, "wrong file format magic {}", magic),
+            Error::UnsupportedVersion(version) => write!(f, "unsupported file format version {}", version),
+            Error::Corrupted(ref msg) => write!(f, "corrupted model: {}", msg),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// Reads the magic and the version of a model file as `checkModelFile` does.
+pub fn read_header<R: Read>(reader: &mut R) -> Result<i32, Error> {
+    let magic = read_i32(reader)?;
+    if magic != FILEFORMAT_MAGIC {
+        return Err(Error::WrongMagic(magic));
+    }
+
+    let version = read_i32(reader)?;
+    if version > VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+
+    Ok(version)
+}
+
+/// A whole model file.
+#[derive(Debug, Clone)]
+pub struct Model {
+    pub version: i32,
+    pub args: Args,
+    pub dictionary: Dictionary,
+    pub input: Matrix,
+    pub qout: bool,
+    pub output: Matrix,
+}
+
+impl Model {
+    pub fn open(path: &Path) -> Result<Model, Error> {
+        Model::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn read<R: Read>(reader: &mut R) -> Result<Model, Error> {
+        let version = read_header(reader)?;
+
+        let mut args = Args::read(reader)?;
+        if version == 11 && args.model == ModelKind::Supervised {
+            // old supervised models do not use char ngrams
+            args.maxn = 0;
+        }
+
+        let dictionary = Dictionary::read(reader)?;
+
+        let input = if read_bool(reader)? {
+            Matrix::Quantized(QuantizedMatrix::read(reader)?)
+        } else {
+            Matrix::Dense(DenseMatrix::read(reader)?)
+        };
+
+        if !input.is_quantized() && dictionary.is_pruned() {
+            return Err(Error::Corrupted(String::from("a pruned dictionary requires a quantized input matrix")));
+        }
+
+        let qout = read_bool(reader)?;
+
+        let output = if input.is_quantized() && qout {
+            Matrix::Quantized(QuantizedMatrix::read(reader)?)
+        } else {
+            Matrix::Dense(DenseMatrix::read(reader)?)
+        };
+
+        if input.cols() != args.dim as usize || output.cols() != args.dim as usize {
+            return Err(Error::Corrupted(format!("matrices {}x{} and {}x{} do not match dimension {}",
+                                                input.rows(), input.cols(), output.rows(), output.cols(), args.dim)));
+        }
+
+        Ok(Model {
+            version,
+            args,
+            dictionary,
+            input,
+            qout,
+            output,
+        })
+    }
+
+    pub fn is_quantized(&self) -> bool {
+        self.input.is_quantized()
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_read_header() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&FILEFORMAT_MAGIC.to_le_bytes());
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+
+        assert_eq!(read_header(&mut bytes.as_slice()).ok(), Some(VERSION), "check valid header");
+
+        match read_header(&mut &bytes[4..]) {
+            Err(Error::WrongMagic(_)) => assert!(true),
+            res => assert!(false, "failed to raise a wrong magic error, got {:?}", res),
+        }
+
+        bytes[4..].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        match read_header(&mut bytes.as_slice()) {
+            Err(Error::UnsupportedVersion(v)) => assert_eq!(v, VERSION + 1),
+            res => assert!(false, "failed to raise an unsupported version error, got {:?}", res),
+        }
+
+        match read_header(&mut &bytes[..6]) {
+            Err(Error::Io(_)) => assert!(true),
+            res => assert!(false, "failed to raise an io error, got {:?}", res),
+        }
+    }
+}
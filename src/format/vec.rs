@@ -0,0 +1,327 @@
+//! Streaming reader and writer of the word2vec-style text format of `.vec` files:
+//! a `rows dim` header followed by one `word v1 ... vdim` line per word.
+
+use std::error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use format::DenseMatrix;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Header { line: usize, msg: String },
+    Parse { line: usize, msg: String },
+    Dimension { line: usize, expected: usize, got: usize },
+    RowCount { expected: usize, got: usize },
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref err) => write!(f, "{}", err),
+            Error::Header { line, ref msg } => write!(f, "line {}: wrong header: {}", line, msg),
+            Error::Parse { line, ref msg } => write!(f, "line {}: {}", line, msg),
+            Error::Dimension { line, expected, got } => write!(f, "line {}: expected {} values, got {}", line, expected, got),
+            Error::RowCount { expected, got } => write!(f, "expected {} rows, got {}", expected, got),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+fn fields(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c| c == ' ' || c == '\t')
+        .filter(|field| !field.is_empty())
+}
+
+/// Reads a `.vec` file row by row.
+pub struct Reader<R: BufRead> {
+    reader: R,
+    rows: usize,
+    dim: usize,
+    line: usize,
+    read: usize,
+    done: bool,
+    buf: String,
+}
+
+impl Reader<BufReader<File>> {
+    pub fn open(path: &Path) -> Result<Reader<BufReader<File>>, Error> {
+        Reader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Parses the header; rows are read lazily by iterating the reader.
+    pub fn new(mut reader: R) -> Result<Reader<R>, Error> {
+        let mut buf = String::new();
+        reader.read_line(&mut buf)?;
+
+        let header: Vec<&str> = fields(buf.trim_end_matches(|c| c == '\n' || c == '\r')).collect();
+        if header.len() != 2 {
+            return Err(Error::Header { line: 1, msg: format!("expected `rows dim`, got {:?}", buf.trim_end()) });
+        }
+
+        let rows = header[0].parse()
+            .map_err(|_| Error::Header { line: 1, msg: format!("wrong number of rows {:?}", header[0]) })?;
+        let dim = header[1].parse()
+            .map_err(|_| Error::Header { line: 1, msg: format!("wrong dimension {:?}", header[1]) })?;
+        if usize::checked_mul(rows, dim).is_none() {
+            return Err(Error::Header { line: 1, msg: format!("too many values for {} rows of dimension {}", rows, dim) });
+        }
+
+        Ok(Reader {
+            reader,
+            rows,
+            dim,
+            line: 1,
+            read: 0,
+            done: false,
+            buf,
+        })
+    }
+
+    /// The number of rows declared by the header.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// Reads all rows into a list of words and a matrix with the vector of each word in a row.
+    pub fn read_all(self) -> Result<(Vec<String>, DenseMatrix), Error> {
+        let dim = self.dim;
+        // the header is not trusted for the allocation, the rows are counted as they are read
+        let mut words = Vec::new();
+        let mut data = Vec::new();
+
+        for row in self {
+            let (word, vector) = row?;
+
+            words.push(word);
+            data.extend_from_slice(&vector);
+        }
+
+        let matrix = DenseMatrix {
+            rows: words.len(),
+            cols: dim,
+            data,
+        };
+
+        Ok((words, matrix))
+    }
+
+    fn parse_row(&self) -> Result<(String, Vec<f32>), Error> {
+        let mut fields = fields(self.buf.trim_end_matches(|c| c == '\n' || c == '\r'));
+
+        let word = match fields.next() {
+            Some(word) => word.to_owned(),
+            None => return Err(Error::Parse { line: self.line, msg: String::from("empty line") }),
+        };
+
+        // not preallocated with the dimension of the header, which is not trusted
+        let mut vector = Vec::new();
+        for field in fields {
+            let v = field.parse::<f32>()
+                .map_err(|_| Error::Parse { line: self.line, msg: format!("wrong value {:?} of {:?}", field, word) })?;
+            vector.push(v);
+        }
+
+        if vector.len() != self.dim {
+            return Err(Error::Dimension { line: self.line, expected: self.dim, got: vector.len() });
+        }
+
+        Ok((word, vector))
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = Result<(String, Vec<f32>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        // blank lines, such as a trailing one, are not rows
+        let len = loop {
+            self.buf.clear();
+            let len = match self.reader.read_line(&mut self.buf) {
+                Ok(len) => len,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(Error::Io(err)));
+                }
+            };
+            self.line += 1;
+
+            if len == 0 || !self.buf.trim().is_empty() {
+                break len;
+            }
+        };
+
+        if len == 0 {
+            self.done = true;
+            return if self.read < self.rows {
+                Some(Err(Error::RowCount { expected: self.rows, got: self.read }))
+            } else {
+                None
+            };
+        }
+
+        self.read += 1;
+        if self.read > self.rows {
+            self.done = true;
+            return Some(Err(Error::RowCount { expected: self.rows, got: self.read }));
+        }
+
+        let row = self.parse_row();
+        if row.is_err() {
+            self.done = true;
+        }
+
+        Some(row)
+    }
+}
+
+/// Writes a `.vec` file row by row, checking the rows against the header.
+pub struct Writer<W: Write> {
+    writer: W,
+    rows: usize,
+    dim: usize,
+    written: usize,
+}
+
+impl Writer<io::BufWriter<File>> {
+    pub fn create(path: &Path, rows: usize, dim: usize) -> Result<Writer<io::BufWriter<File>>, Error> {
+        Writer::new(io::BufWriter::new(File::create(path)?), rows, dim)
+    }
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(mut writer: W, rows: usize, dim: usize) -> Result<Writer<W>, Error> {
+        writeln!(writer, "{} {}", rows, dim)?;
+
+        Ok(Writer {
+            writer,
+            rows,
+            dim,
+            written: 0,
+        })
+    }
+
+    pub fn write(&mut self, word: &str, vector: &[f32]) -> Result<(), Error> {
+        let line = self.written + 2;
+
+        if word.is_empty() || word.contains(|c: char| c.is_whitespace()) {
+            return Err(Error::Parse { line, msg: format!("word {:?} is empty or contains whitespace", word) });
+        }
+        if vector.len() != self.dim {
+            return Err(Error::Dimension { line, expected: self.dim, got: vector.len() });
+        }
+        if self.written == self.rows {
+            return Err(Error::RowCount { expected: self.rows, got: self.written + 1 });
+        }
+
+        write!(self.writer, "{}", word)?;
+        for v in vector {
+            write!(self.writer, " {}", v)?;
+        }
+        writeln!(self.writer)?;
+
+        self.written += 1;
+
+        Ok(())
+    }
+
+    /// Checks that all declared rows were written and flushes the writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        if self.written != self.rows {
+            return Err(Error::RowCount { expected: self.rows, got: self.written });
+        }
+
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_vec_write_read() {
+        let mut writer = Writer::new(Vec::new(), 2, 3).unwrap();
+        writer.write("кот", &[1.0, -0.5, 0.25]).unwrap();
+        writer.write("дуб", &[0.125, 3.0, -2.0]).unwrap();
+
+        assert!(writer.write("цепь", &[1.0, 2.0]).is_err(), "check dimension");
+        assert!(writer.write("цепь", &[1.0, 2.0, 3.0]).is_err(), "check row count");
+
+        let bytes = writer.finish().unwrap();
+        let reader = Reader::new(bytes.as_slice()).unwrap();
+        assert_eq!((reader.rows(), reader.dim()), (2, 3), "check header");
+
+        let (words, matrix) = reader.read_all().unwrap();
+        assert_eq!(words, vec!["кот", "дуб"], "check words");
+        assert_eq!(matrix.row(1), &[0.125, 3.0, -2.0], "check vectors");
+    }
+
+    #[test]
+    fn test_vec_read_errors() {
+        match Reader::new("2\n".as_bytes()) {
+            Err(Error::Header { line: 1, .. }) => assert!(true),
+            res => assert!(false, "failed to raise a header error, got {:?}", res.map(|_| ())),
+        }
+
+        let rows: Vec<_> = Reader::new("2 2\nкот 1 2\nдуб 1 2 3\n".as_bytes()).unwrap().collect();
+        match rows[1] {
+            Err(Error::Dimension { line: 3, expected: 2, got: 3 }) => assert!(true),
+            ref res => assert!(false, "failed to raise a dimension error, got {:?}", res),
+        }
+
+        let rows: Vec<_> = Reader::new("2 2\nкот 1 x\n".as_bytes()).unwrap().collect();
+        match rows[0] {
+            Err(Error::Parse { line: 2, .. }) => assert!(true),
+            ref res => assert!(false, "failed to raise a parse error, got {:?}", res),
+        }
+
+        let rows: Vec<_> = Reader::new("2 2\nкот 1 2\n".as_bytes()).unwrap().collect();
+        match rows[1] {
+            Err(Error::RowCount { expected: 2, got: 1 }) => assert!(true),
+            ref res => assert!(false, "failed to raise a row count error, got {:?}", res),
+        }
+
+        match Reader::new(format!("{} 2\n", usize::max_value()).as_bytes()) {
+            Err(Error::Header { line: 1, .. }) => assert!(true),
+            res => assert!(false, "failed to raise an overflow error, got {:?}", res.map(|_| ())),
+        }
+
+        let rows: Vec<_> = Reader::new("1 1099511627776\nw 1\n".as_bytes()).unwrap().collect();
+        match rows[0] {
+            Err(Error::Dimension { line: 2, expected: 1099511627776, got: 1 }) => assert!(true),
+            ref res => assert!(false, "failed to raise a dimension error for a huge dimension, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_vec_read_blank_lines() {
+        let reader = Reader::new("2 2\nкот 1 2\n\nдуб 3 4\n\n".as_bytes()).unwrap();
+
+        let (words, matrix) = reader.read_all().unwrap();
+        assert_eq!(words, vec!["кот", "дуб"], "check words");
+        assert_eq!(matrix.row(1), &[3.0, 4.0], "check vectors");
+    }
+}
@@ -0,0 +1,56 @@
+//! Metadata of a loaded model.
+
+use format::{Args, Loss, ModelKind};
+use {Err, FastText};
+
+/// What a model file declares about the model and its training.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModelInfo {
+    /// The file format version of the model file.
+    pub version: i32,
+    /// The number of words of the vocabulary.
+    pub nwords: i32,
+    pub nlabels: i32,
+    /// The number of tokens of the training data.
+    pub ntokens: i64,
+    pub quantized: bool,
+    /// The training arguments saved in the model file.
+    pub args: Args,
+}
+
+impl ModelInfo {
+    pub fn dim(&self) -> i32 {
+        self.args.dim
+    }
+
+    pub fn model(&self) -> ModelKind {
+        self.args.model
+    }
+
+    pub fn loss(&self) -> Loss {
+        self.args.loss
+    }
+
+    pub fn word_ngrams(&self) -> i32 {
+        self.args.word_ngrams
+    }
+
+    pub fn minn(&self) -> i32 {
+        self.args.minn
+    }
+
+    pub fn maxn(&self) -> i32 {
+        self.args.maxn
+    }
+
+    pub fn bucket(&self) -> i32 {
+        self.args.bucket
+    }
+}
+
+impl FastText {
+    pub fn info(&self) -> Result<ModelInfo, Err> {
+        self.0.model_info()
+    }
+}
@@ -3,20 +3,59 @@
 #![feature(plugin)]
 
 extern crate libc;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 extern crate test;
 extern crate wordvector as wordvector_base;
 
 mod testing;
+mod result;
+#[cfg(any(feature = "pure-rust", test))]
+mod native;
+
+#[cfg(not(feature = "pure-rust"))]
 mod string;
+#[cfg(not(feature = "pure-rust"))]
+mod fasttext;
+#[cfg(feature = "pure-rust")]
+#[path = "pure/fasttext.rs"]
 mod fasttext;
 
+pub mod ann;
+pub mod classifier;
+#[cfg(not(feature = "pure-rust"))]
+pub mod dictionary;
+#[cfg(feature = "pure-rust")]
+#[path = "pure/dictionary.rs"]
 pub mod dictionary;
+pub mod embedding;
+pub mod format;
+pub mod info;
+pub mod matrix;
+#[cfg(not(feature = "pure-rust"))]
 pub mod predict;
+#[cfg(feature = "pure-rust")]
+#[path = "pure/predict.rs"]
+pub mod predict;
+pub mod sentence;
+#[cfg(not(feature = "pure-rust"))]
+pub mod train;
+#[cfg(not(feature = "pure-rust"))]
+pub mod vector;
+#[cfg(feature = "pure-rust")]
+#[path = "pure/vector.rs"]
 pub mod vector;
+pub mod word2vec;
 pub mod wordvector;
 
 use fasttext::FastTextWrapper;
 
+pub use format::inspect::{inspect_file, FileReport};
+pub use result::Err;
+
 pub struct FastText(FastTextWrapper);
 
 impl Default for FastText {
@@ -0,0 +1,104 @@
+//! Bulk embedding of words and sentences into a row-major matrix.
+
+use std::slice::Chunks;
+
+use {Err, FastText};
+
+/// A row-major matrix of `rows` embeddings of dimension `cols`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f32>,
+}
+
+impl Matrix {
+    /// Panics if `data` is not of `rows * cols` elements.
+    pub fn new(rows: usize, cols: usize, data: Vec<f32>) -> Matrix {
+        assert_eq!(data.len(), rows * cols, "matrix data doesn't match its shape");
+
+        Matrix { rows, cols, data }
+    }
+
+    pub fn zeros(rows: usize, cols: usize) -> Matrix {
+        Matrix::new(rows, cols, vec![0f32; rows * cols])
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, i: usize) -> &[f32] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn row_mut(&mut self, i: usize) -> &mut [f32] {
+        &mut self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn iter_rows(&self) -> Chunks<f32> {
+        self.data.chunks(self.cols.max(1))
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        &self.data
+    }
+
+    pub fn into_vec(self) -> Vec<f32> {
+        self.data
+    }
+}
+
+impl FastText {
+    /// The vectors of `words`, one row per word, looked up in a single call into the model.
+    pub fn embed_words(&self, words: &[&str]) -> Result<Matrix, Err> {
+        self.embed_words_parallel(words, 1)
+    }
+
+    /// `embed_words` with the lookups split between `threads` threads.
+    pub fn embed_words_parallel(&self, words: &[&str], threads: usize) -> Result<Matrix, Err> {
+        let mut matrix = Matrix::zeros(words.len(), self.dimension());
+        self.0.embed_words(words, &mut matrix.data, threads)?;
+
+        Ok(matrix)
+    }
+
+    /// The sentence vectors of `texts`, one row per text, with the semantics of the model kind.
+    pub fn embed_sentences(&self, texts: &[&str]) -> Result<Matrix, Err> {
+        self.embed_sentences_parallel(texts, 1)
+    }
+
+    /// `embed_sentences` with the lookups split between `threads` threads.
+    pub fn embed_sentences_parallel(&self, texts: &[&str], threads: usize) -> Result<Matrix, Err> {
+        let mut matrix = Matrix::zeros(texts.len(), self.dimension());
+        self.0.embed_sentences(texts, &mut matrix.data, threads)?;
+
+        Ok(matrix)
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_matrix_rows() {
+        let mut matrix = Matrix::new(2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        matrix.row_mut(0)[0] = 0.0;
+
+        assert_eq!(matrix.row(0), &[0.0, 2.0, 3.0]);
+        assert_eq!(matrix.row(1), &[4.0, 5.0, 6.0]);
+        assert_eq!(matrix.iter_rows().count(), 2);
+        assert_eq!(Matrix::zeros(0, 3).iter_rows().count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_matrix_wrong_shape() {
+        Matrix::new(2, 3, vec![0.0; 5]);
+    }
+}
@@ -0,0 +1,288 @@
+//! Replicas of the libstdc++ algorithms fastText relies on.
+//!
+//! `std::sort` and the heap functions do not preserve the order of equal elements, so the
+//! dictionary order and the order of tied predictions depend on their exact implementation.
+
+use std::mem;
+
+const SORT_THRESHOLD: usize = 16;
+
+/// `std::push_heap`
+pub(crate) fn push_heap<T: Copy, F: Fn(&T, &T) -> bool>(heap: &mut [T], comp: &F) {
+    let len = heap.len();
+    if len > 1 {
+        let value = heap[len - 1];
+        push_hole(heap, len - 1, 0, value, comp);
+    }
+}
+
+/// `std::pop_heap`
+pub(crate) fn pop_heap<T: Copy, F: Fn(&T, &T) -> bool>(heap: &mut [T], comp: &F) {
+    let len = heap.len();
+    if len > 1 {
+        pop_hole(heap, len - 1, comp);
+    }
+}
+
+/// `std::make_heap`
+pub(crate) fn make_heap<T: Copy, F: Fn(&T, &T) -> bool>(heap: &mut [T], comp: &F) {
+    let len = heap.len();
+    if len < 2 {
+        return;
+    }
+
+    let mut parent = (len - 2) / 2;
+    loop {
+        let value = heap[parent];
+        adjust_heap(heap, parent, len, value, comp);
+
+        if parent == 0 {
+            return;
+        }
+        parent -= 1;
+    }
+}
+
+/// `std::sort_heap`
+pub(crate) fn sort_heap<T: Copy, F: Fn(&T, &T) -> bool>(heap: &mut [T], comp: &F) {
+    let mut last = heap.len();
+    while last > 1 {
+        last -= 1;
+        pop_hole(heap, last, comp);
+    }
+}
+
+/// `std::sort`, an introsort finished by an insertion sort.
+pub(crate) fn sort<T: Copy, F: Fn(&T, &T) -> bool>(v: &mut [T], comp: &F) {
+    let len = v.len();
+    if len > 0 {
+        introsort_loop(v, 0, len, 2 * lg(len), comp);
+        final_insertion_sort(v, comp);
+    }
+}
+
+fn lg(n: usize) -> usize {
+    mem::size_of::<usize>() * 8 - 1 - n.leading_zeros() as usize
+}
+
+fn push_hole<T: Copy, F: Fn(&T, &T) -> bool>(heap: &mut [T], mut hole: usize, top: usize, value: T, comp: &F) {
+    while hole > top {
+        let parent = (hole - 1) / 2;
+        if !comp(&heap[parent], &value) {
+            break;
+        }
+
+        heap[hole] = heap[parent];
+        hole = parent;
+    }
+
+    heap[hole] = value;
+}
+
+fn pop_hole<T: Copy, F: Fn(&T, &T) -> bool>(heap: &mut [T], last: usize, comp: &F) {
+    let value = heap[last];
+    heap[last] = heap[0];
+    adjust_heap(heap, 0, last, value, comp);
+}
+
+fn adjust_heap<T: Copy, F: Fn(&T, &T) -> bool>(heap: &mut [T], mut hole: usize, len: usize, value: T, comp: &F) {
+    let top = hole;
+    let mut second = hole as isize;
+    let len = len as isize;
+
+    while second < (len - 1) / 2 {
+        second = 2 * (second + 1);
+        if comp(&heap[second as usize], &heap[second as usize - 1]) {
+            second -= 1;
+        }
+
+        heap[hole] = heap[second as usize];
+        hole = second as usize;
+    }
+
+    if len & 1 == 0 && second == (len - 2) / 2 {
+        second = 2 * (second + 1);
+        heap[hole] = heap[second as usize - 1];
+        hole = second as usize - 1;
+    }
+
+    push_hole(heap, hole, top, value, comp);
+}
+
+fn introsort_loop<T: Copy, F: Fn(&T, &T) -> bool>(v: &mut [T], first: usize, mut last: usize, mut depth: usize, comp: &F) {
+    while last - first > SORT_THRESHOLD {
+        if depth == 0 {
+            make_heap(&mut v[first..last], comp);
+            sort_heap(&mut v[first..last], comp);
+            return;
+        }
+        depth -= 1;
+
+        let cut = partition_pivot(v, first, last, comp);
+        introsort_loop(v, cut, last, depth, comp);
+        last = cut;
+    }
+}
+
+fn partition_pivot<T: Copy, F: Fn(&T, &T) -> bool>(v: &mut [T], first: usize, last: usize, comp: &F) -> usize {
+    let mid = first + (last - first) / 2;
+    move_median_to_first(v, first, first + 1, mid, last - 1, comp);
+
+    unguarded_partition(v, first + 1, last, first, comp)
+}
+
+fn move_median_to_first<T: Copy, F: Fn(&T, &T) -> bool>(v: &mut [T], result: usize, a: usize, b: usize, c: usize, comp: &F) {
+    if comp(&v[a], &v[b]) {
+        if comp(&v[b], &v[c]) {
+            v.swap(result, b)
+        } else if comp(&v[a], &v[c]) {
+            v.swap(result, c)
+        } else {
+            v.swap(result, a)
+        }
+    } else if comp(&v[a], &v[c]) {
+        v.swap(result, a)
+    } else if comp(&v[b], &v[c]) {
+        v.swap(result, c)
+    } else {
+        v.swap(result, b)
+    }
+}
+
+fn unguarded_partition<T: Copy, F: Fn(&T, &T) -> bool>(v: &mut [T], mut first: usize, mut last: usize, pivot: usize, comp: &F) -> usize {
+    loop {
+        while comp(&v[first], &v[pivot]) {
+            first += 1;
+        }
+
+        last -= 1;
+        while comp(&v[pivot], &v[last]) {
+            last -= 1;
+        }
+
+        if first >= last {
+            return first;
+        }
+
+        v.swap(first, last);
+        first += 1;
+    }
+}
+
+fn final_insertion_sort<T: Copy, F: Fn(&T, &T) -> bool>(v: &mut [T], comp: &F) {
+    if v.len() > SORT_THRESHOLD {
+        insertion_sort(&mut v[..SORT_THRESHOLD], comp);
+        for i in SORT_THRESHOLD..v.len() {
+            unguarded_linear_insert(v, i, comp);
+        }
+    } else {
+        insertion_sort(v, comp);
+    }
+}
+
+fn insertion_sort<T: Copy, F: Fn(&T, &T) -> bool>(v: &mut [T], comp: &F) {
+    for i in 1..v.len() {
+        if comp(&v[i], &v[0]) {
+            let value = v[i];
+            for j in (0..i).rev() {
+                v[j + 1] = v[j];
+            }
+            v[0] = value;
+        } else {
+            unguarded_linear_insert(v, i, comp);
+        }
+    }
+}
+
+fn unguarded_linear_insert<T: Copy, F: Fn(&T, &T) -> bool>(v: &mut [T], mut last: usize, comp: &F) {
+    let value = v[last];
+
+    while comp(&value, &v[last - 1]) {
+        v[last] = v[last - 1];
+        last -= 1;
+    }
+
+    v[last] = value;
+}
+
+/// `std::minstd_rand`
+pub(crate) struct MinstdRand(u64);
+
+impl MinstdRand {
+    const MODULUS: u64 = 2147483647;
+
+    pub(crate) fn new(seed: u64) -> MinstdRand {
+        match seed % MinstdRand::MODULUS {
+            0 => MinstdRand(1),
+            s => MinstdRand(s),
+        }
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0 * 48271 % MinstdRand::MODULUS;
+        self.0
+    }
+}
+
+/// `std::uniform_real_distribution<double>(a, b)` driven by `std::minstd_rand`.
+///
+/// `std::generate_canonical<double, 53>` takes two draws of the 31-bit generator.
+pub(crate) fn uniform(rng: &mut MinstdRand, a: f64, b: f64) -> f64 {
+    const RANGE: f64 = 2147483646.0;
+
+    let mut sum = 0f64;
+    let mut tmp = 1f64;
+    for _ in 0..2 {
+        sum += (rng.next() - 1) as f64 * tmp;
+        tmp *= RANGE;
+    }
+
+    let mut canonical = sum / tmp;
+    if canonical >= 1.0 {
+        canonical = 1.0 - ::std::f64::EPSILON / 2.0;
+    }
+
+    canonical * (b - a) + a
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_sort() {
+        let comp = |a: &(i64, usize), b: &(i64, usize)| a.0 > b.0;
+
+        let mut v: Vec<(i64, usize)> = (0..100).map(|i| ((i * 7919 % 13) as i64, i)).collect();
+        sort(&mut v, &comp);
+
+        assert!(v.windows(2).all(|w| w[0].0 >= w[1].0), "check order");
+        assert_eq!(v.len(), 100, "check length");
+    }
+
+    #[test]
+    fn test_heap() {
+        let comp = |a: &(f32, i32), b: &(f32, i32)| a.0 > b.0;
+
+        let mut heap = Vec::new();
+        for (i, &x) in [0.3f32, 0.1, 0.7, 0.5, 0.2].iter().enumerate() {
+            heap.push((x, i as i32));
+            push_heap(&mut heap, &comp);
+            if heap.len() > 3 {
+                pop_heap(&mut heap, &comp);
+                heap.pop();
+            }
+        }
+        sort_heap(&mut heap, &comp);
+
+        assert_eq!(heap, vec![(0.7, 2), (0.5, 3), (0.3, 0)], "check top k");
+    }
+
+    #[test]
+    fn test_minstd_rand() {
+        let mut rng = MinstdRand::new(1);
+
+        assert_eq!(rng.next(), 48271);
+        assert_eq!(rng.next(), 182605794);
+    }
+}
@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+
+use format::{self, Args, EntryType};
+use native::cxx;
+
+pub(crate) const EOS: &'static str = "</s>";
+const BOW: &'static str = "<";
+const EOW: &'static str = ">";
+const LABEL: &'static str = "__label__";
+
+/// FNV-1a over the bytes of a word, sign-extending each byte as the C++ `char` does.
+pub(crate) fn hash(bytes: &[u8]) -> u32 {
+    let mut h = 2166136261u32;
+
+    for &b in bytes {
+        h ^= b as i8 as u32;
+        h = h.wrapping_mul(16777619);
+    }
+
+    h
+}
+
+fn is_delimiter(c: u8) -> bool {
+    match c {
+        b' ' | b'\n' | b'\r' | b'\t' | 0x0b | 0x0c | 0 => true,
+        _ => false,
+    }
+}
+
+/// Splits a text into tokens as `Dictionary::readWord` does: a line break is an EOS token.
+pub(crate) fn tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+
+    for (i, &c) in text.as_bytes().iter().enumerate() {
+        if is_delimiter(c) {
+            if let Some(s) = start.take() {
+                tokens.push(&text[s..i]);
+            }
+            if c == b'\n' {
+                tokens.push(EOS);
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+
+    if let Some(s) = start {
+        tokens.push(&text[s..]);
+    }
+
+    tokens
+}
+
+struct Entry {
+    word: String,
+    count: i64,
+    entry_type: EntryType,
+    subwords: Vec<i32>,
+}
+
+/// The dictionary of a loaded model, `Dictionary` in fastText.
+pub(crate) struct Dictionary {
+    entries: Vec<Entry>,
+    word2int: HashMap<String, i32>,
+    nwords: i32,
+    nlabels: i32,
+    ntokens: i64,
+    pruneidx_size: i64,
+    pruneidx: HashMap<i32, i32>,
+    bucket: i32,
+    minn: i32,
+    maxn: i32,
+    word_ngrams: i32,
+}
+
+impl Dictionary {
+    pub(crate) fn new(dict: format::Dictionary, args: &Args) -> Dictionary {
+        let mut dictionary = Dictionary {
+            entries: dict.entries.into_iter()
+                .map(|entry| Entry {
+                    word: entry.word,
+                    count: entry.count,
+                    entry_type: entry.entry_type,
+                    subwords: vec![],
+                })
+                .collect(),
+            word2int: HashMap::new(),
+            nwords: dict.nwords,
+            nlabels: dict.nlabels,
+            ntokens: dict.ntokens,
+            pruneidx_size: dict.pruneidx_size,
+            pruneidx: dict.pruneidx.into_iter().collect(),
+            bucket: args.bucket,
+            minn: args.minn,
+            maxn: args.maxn,
+            word_ngrams: args.word_ngrams,
+        };
+
+        dictionary.init_word2int();
+        dictionary.init_ngrams();
+
+        dictionary
+    }
+
+    fn init_word2int(&mut self) {
+        self.word2int = self.entries.iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.word.clone(), i as i32))
+            .collect();
+    }
+
+    fn init_ngrams(&mut self) {
+        for i in 0..self.entries.len() {
+            let mut subwords = vec![i as i32];
+
+            if self.entries[i].word != EOS {
+                let word = format!("{}{}{}", BOW, self.entries[i].word, EOW);
+                self.compute_subwords(word.as_bytes(), &mut subwords);
+            }
+
+            self.entries[i].subwords = subwords;
+        }
+    }
+
+    pub(crate) fn nwords(&self) -> i32 {
+        self.nwords
+    }
+
+    pub(crate) fn nlabels(&self) -> i32 {
+        self.nlabels
+    }
+
+    pub(crate) fn ntokens(&self) -> i64 {
+        self.ntokens
+    }
+
+    pub(crate) fn id(&self, word: &str) -> Option<i32> {
+        self.word2int.get(word).cloned()
+    }
+
+    pub(crate) fn word(&self, id: i32) -> Option<&str> {
+        self.entries.get(id as usize).map(|entry| entry.word.as_str())
+    }
+
+    pub(crate) fn label(&self, lid: i32) -> &str {
+        self.entries[(lid + self.nwords) as usize].word.as_str()
+    }
+
+    pub(crate) fn counts(&self, entry_type: EntryType) -> Vec<i64> {
+        self.entries.iter()
+            .filter(|entry| entry.entry_type == entry_type)
+            .map(|entry| entry.count)
+            .collect()
+    }
+
+    fn entry_type(&self, word: &str) -> EntryType {
+        if word.starts_with(LABEL) {
+            EntryType::Label
+        } else {
+            EntryType::Word
+        }
+    }
+
+    /// The input rows of a word: the word itself and its character n-grams, `getSubwords`.
+    pub(crate) fn subwords(&self, word: &str) -> Vec<i32> {
+        if let Some(id) = self.id(word) {
+            return self.entries[id as usize].subwords.clone();
+        }
+
+        let mut ngrams = Vec::new();
+        if word != EOS {
+            let word = format!("{}{}{}", BOW, word, EOW);
+            self.compute_subwords(word.as_bytes(), &mut ngrams);
+        }
+
+        ngrams
+    }
+
+    /// The input rows of a word with the strings they stand for, `getSubwords` with substrings.
+    pub(crate) fn subword_strings(&self, word: &str) -> Vec<(i32, String)> {
+        let mut ngrams = Vec::new();
+        let mut substrings = Vec::new();
+
+        if let Some(id) = self.id(word) {
+            ngrams.push(id);
+            substrings.push(word.to_owned());
+        }
+
+        if word != EOS {
+            let word = format!("{}{}{}", BOW, word, EOW);
+            self.compute_subwords_with(word.as_bytes(), &mut ngrams, Some(&mut substrings));
+        }
+
+        ngrams.into_iter().zip(substrings).collect()
+    }
+
+    /// The input rows and the labels of a line of text, `getLine` of a supervised model.
+    pub(crate) fn line(&self, text: &str) -> (Vec<i32>, Vec<i32>) {
+        let mut words = Vec::new();
+        let mut labels = Vec::new();
+        let mut word_hashes = Vec::new();
+
+        for token in tokens(text) {
+            let h = hash(token.as_bytes());
+            let wid = self.id(token);
+            let entry_type = match wid {
+                Some(id) => self.entries[id as usize].entry_type,
+                None => self.entry_type(token),
+            };
+
+            match entry_type {
+                EntryType::Word => {
+                    self.add_subwords(&mut words, token, wid);
+                    word_hashes.push(h as i32);
+                }
+                EntryType::Label => {
+                    if let Some(id) = wid {
+                        labels.push(id - self.nwords);
+                    }
+                }
+            }
+
+            if token == EOS {
+                break;
+            }
+        }
+
+        self.add_word_ngrams(&mut words, &word_hashes);
+
+        (words, labels)
+    }
+
+    fn add_subwords(&self, line: &mut Vec<i32>, token: &str, wid: Option<i32>) {
+        match wid {
+            None => if token != EOS {
+                let word = format!("{}{}{}", BOW, token, EOW);
+                self.compute_subwords(word.as_bytes(), line);
+            },
+            Some(id) => if self.maxn <= 0 {
+                line.push(id);
+            } else {
+                line.extend_from_slice(&self.entries[id as usize].subwords);
+            },
+        }
+    }
+
+    fn add_word_ngrams(&self, line: &mut Vec<i32>, hashes: &[i32]) {
+        if self.bucket <= 0 {
+            return;
+        }
+
+        for i in 0..hashes.len() {
+            let mut h = hashes[i] as i64 as u64;
+
+            for j in (i + 1)..hashes.len() {
+                if j >= i + self.word_ngrams.max(0) as usize {
+                    break;
+                }
+
+                h = h.wrapping_mul(116049371).wrapping_add(hashes[j] as i64 as u64);
+                self.push_hash(line, (h % self.bucket as u64) as i32);
+            }
+        }
+    }
+
+    fn compute_subwords(&self, word: &[u8], ngrams: &mut Vec<i32>) {
+        self.compute_subwords_with(word, ngrams, None)
+    }
+
+    fn compute_subwords_with(&self, word: &[u8], ngrams: &mut Vec<i32>, mut substrings: Option<&mut Vec<String>>) {
+        if self.bucket <= 0 || self.maxn <= 0 {
+            return;
+        }
+
+        let minn = self.minn.max(0) as usize;
+        let maxn = self.maxn as usize;
+
+        for i in 0..word.len() {
+            if word[i] & 0xC0 == 0x80 {
+                continue;
+            }
+
+            let mut j = i;
+            let mut n = 1;
+            while j < word.len() && n <= maxn {
+                j += 1;
+                while j < word.len() && word[j] & 0xC0 == 0x80 {
+                    j += 1;
+                }
+
+                if n >= minn && !(n == 1 && (i == 0 || j == word.len())) {
+                    let h = hash(&word[i..j]) % self.bucket as u32;
+                    let len = ngrams.len();
+                    self.push_hash(ngrams, h as i32);
+
+                    if let Some(ref mut substrings) = substrings {
+                        if ngrams.len() > len {
+                            substrings.push(String::from_utf8_lossy(&word[i..j]).into_owned());
+                        }
+                    }
+                }
+
+                n += 1;
+            }
+        }
+    }
+
+    fn push_hash(&self, hashes: &mut Vec<i32>, id: i32) {
+        if self.pruneidx_size == 0 || id < 0 {
+            return;
+        }
+
+        let id = if self.pruneidx_size > 0 {
+            match self.pruneidx.get(&id) {
+                Some(&id) => id,
+                None => return,
+            }
+        } else {
+            id
+        };
+
+        hashes.push(self.nwords + id);
+    }
+
+    /// Counts a word, appending it if unknown, as `Dictionary::add` does.
+    pub(crate) fn add(&mut self, word: &str) {
+        self.ntokens += 1;
+
+        match self.id(word) {
+            Some(id) => self.entries[id as usize].count += 1,
+            None => {
+                let entry_type = self.entry_type(word);
+
+                self.word2int.insert(word.to_owned(), self.entries.len() as i32);
+                self.entries.push(Entry {
+                    word: word.to_owned(),
+                    count: 1,
+                    entry_type,
+                    subwords: vec![],
+                });
+            }
+        }
+    }
+
+    /// Sorts entries by type and count and drops rare ones, as `Dictionary::threshold` does.
+    ///
+    /// Like the C++ version, it keeps the subwords computed for the previous order.
+    pub(crate) fn threshold(&mut self, t: i64, tl: i64) {
+        let mut order: Vec<(u8, i64, usize)> = self.entries.iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.entry_type as u8, entry.count, i))
+            .collect();
+
+        cxx::sort(&mut order, &|e1: &(u8, i64, usize), e2: &(u8, i64, usize)| {
+            if e1.0 != e2.0 {
+                e1.0 < e2.0
+            } else {
+                e1.1 > e2.1
+            }
+        });
+
+        let mut entries: Vec<Option<Entry>> = self.entries.drain(..).map(Some).collect();
+        self.entries = order.into_iter()
+            .filter_map(|(_, _, i)| entries[i].take())
+            .filter(|entry| match entry.entry_type {
+                EntryType::Word => entry.count >= t,
+                EntryType::Label => entry.count >= tl,
+            })
+            .collect();
+
+        self.nwords = self.entries.iter().filter(|entry| entry.entry_type == EntryType::Word).count() as i32;
+        self.nlabels = self.entries.len() as i32 - self.nwords;
+
+        self.init_word2int();
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_tokens() {
+        assert_eq!(tokens("Кащей  над\tзлатом\nчахнет"), vec!["Кащей", "над", "златом", EOS, "чахнет"]);
+        assert_eq!(tokens(" \n"), vec![EOS]);
+        assert!(tokens("").is_empty());
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(hash(b""), 2166136261);
+        assert_eq!(hash(b"a"), 0xe40c292c);
+        assert_eq!(hash("ё".as_bytes()), 1154999063, "check sign extension of non-ASCII bytes");
+    }
+}
@@ -0,0 +1,80 @@
+use format::{Matrix, ProductQuantizer, QuantizedMatrix};
+
+fn row_norm(matrix: &QuantizedMatrix, i: usize) -> f32 {
+    match matrix.npq {
+        Some(ref npq) if matrix.qnorm => npq.centroid(0, matrix.norm_codes[i])[0],
+        _ => 1.0,
+    }
+}
+
+fn add_code(pq: &ProductQuantizer, x: &mut [f32], codes: &[u8], t: usize, alpha: f32) {
+    let nsubq = pq.nsubq as usize;
+    let dsub = pq.dsub as usize;
+    let code = &codes[nsubq * t..nsubq * (t + 1)];
+
+    for m in 0..nsubq {
+        for (n, c) in pq.centroid(m, code[m]).iter().enumerate() {
+            x[m * dsub + n] += alpha * c;
+        }
+    }
+}
+
+fn mul_code(pq: &ProductQuantizer, x: &[f32], codes: &[u8], t: usize, alpha: f32) -> f32 {
+    let nsubq = pq.nsubq as usize;
+    let dsub = pq.dsub as usize;
+    let code = &codes[nsubq * t..nsubq * (t + 1)];
+
+    let mut res = 0f32;
+    for m in 0..nsubq {
+        for (n, c) in pq.centroid(m, code[m]).iter().enumerate() {
+            res += x[m * dsub + n] * c;
+        }
+    }
+
+    res * alpha
+}
+
+/// Adds the row `i` of a matrix to a vector, `Vector::addRow`.
+pub(crate) fn add_row(matrix: &Matrix, vec: &mut [f32], i: usize) {
+    match *matrix {
+        Matrix::Dense(ref m) => {
+            for (v, a) in vec.iter_mut().zip(m.row(i)) {
+                *v += *a;
+            }
+        }
+        Matrix::Quantized(ref m) => add_code(&m.pq, vec, &m.codes, i, row_norm(m, i)),
+    }
+}
+
+/// The dot product of the row `i` of a matrix and a vector, `Matrix::dotRow`.
+pub(crate) fn dot_row(matrix: &Matrix, vec: &[f32], i: usize) -> f32 {
+    match *matrix {
+        Matrix::Dense(ref m) => {
+            let mut res = 0f32;
+            for (a, v) in m.row(i).iter().zip(vec) {
+                res += a * v;
+            }
+            res
+        }
+        Matrix::Quantized(ref m) => mul_code(&m.pq, vec, &m.codes, i, row_norm(m, i)),
+    }
+}
+
+/// `Vector::mul` with a scalar computed in double precision and rounded as a `real` argument.
+pub(crate) fn scale(vec: &mut [f32], a: f64) {
+    let a = a as f32;
+
+    for v in vec.iter_mut() {
+        *v *= a;
+    }
+}
+
+/// `Vector::norm`
+pub(crate) fn norm(vec: &[f32]) -> f32 {
+    let mut sum = 0f32;
+    for v in vec {
+        sum += v * v;
+    }
+
+    sum.sqrt()
+}
@@ -0,0 +1,453 @@
+//! Pure-Rust inference over a model read by `format`, mirroring fastText's `FastText` and `Model`.
+//!
+//! Floating point operations follow the C++ implementation step by step, including the
+//! precision of intermediate values, so both backends produce the same numbers.
+
+mod cxx;
+mod dictionary;
+mod matrix;
+
+use std::io::Read;
+
+use format::{self, Args, DenseMatrix, EntryType, Loss, Matrix, ModelKind};
+
+pub(crate) use self::dictionary::Dictionary;
+
+use self::cxx::MinstdRand;
+use self::matrix::{add_row, dot_row, norm, scale};
+
+const K_ERROR: &'static str = "k needs to be 1 or higher!";
+const MODEL_ERROR: &'static str = "Model needs to be supervised for prediction!";
+
+const SIGMOID_TABLE_SIZE: i64 = 512;
+const MAX_SIGMOID: f32 = 8.0;
+
+/// `std_log` of fastText: the logarithm is computed in double precision.
+fn std_log(x: f32) -> f32 {
+    (x as f64 + 1e-5).ln() as f32
+}
+
+/// The look-up table sigmoid of fastText's `Model::sigmoid`.
+fn sigmoid(x: f32) -> f32 {
+    if x < -MAX_SIGMOID {
+        0.0
+    } else if x > MAX_SIGMOID {
+        1.0
+    } else {
+        let i = ((x + MAX_SIGMOID) * SIGMOID_TABLE_SIZE as f32 / MAX_SIGMOID / 2.0) as i64;
+        let x = (i * 2 * MAX_SIGMOID as i64) as f32 / SIGMOID_TABLE_SIZE as f32 - MAX_SIGMOID;
+
+        (1.0 / (1.0 + (-x).exp() as f64)) as f32
+    }
+}
+
+fn compare_pairs(l: &(f32, i32), r: &(f32, i32)) -> bool {
+    l.0 > r.0
+}
+
+fn is_space(c: u8) -> bool {
+    match c {
+        b' ' | b'\t' | b'\n' | 0x0b | 0x0c | b'\r' => true,
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    left: i32,
+    right: i32,
+    count: i64,
+}
+
+/// The Huffman tree of the hierarchical softmax, `Model::buildTree`.
+fn build_tree(counts: &[i64]) -> Vec<Node> {
+    let osz = counts.len();
+    if osz == 0 {
+        return vec![];
+    }
+
+    let mut tree = vec![Node { left: -1, right: -1, count: 1_000_000_000_000_000 }; 2 * osz - 1];
+    for (node, &count) in tree.iter_mut().zip(counts) {
+        node.count = count;
+    }
+
+    let mut leaf = osz as isize - 1;
+    let mut node = osz;
+    for i in osz..2 * osz - 1 {
+        let mut mini = [0usize; 2];
+
+        for m in mini.iter_mut() {
+            if leaf >= 0 && tree[leaf as usize].count < tree[node].count {
+                *m = leaf as usize;
+                leaf -= 1;
+            } else {
+                *m = node;
+                node += 1;
+            }
+        }
+
+        tree[i].left = mini[0] as i32;
+        tree[i].right = mini[1] as i32;
+        tree[i].count = tree[mini[0]].count + tree[mini[1]].count;
+    }
+
+    tree
+}
+
+/// A loaded model.
+pub(crate) struct Model {
+    version: i32,
+    args: Args,
+    dict: Dictionary,
+    quant: bool,
+    /// The input matrix of fastText's `Model`, used for prediction.
+    wi: Matrix,
+    /// `FastText::input_` once `load_vectors` replaced it; word vectors are taken from it.
+    input: Option<Matrix>,
+    wo: Matrix,
+    tree: Vec<Node>,
+}
+
+impl Model {
+    pub(crate) fn new(model: format::Model) -> Model {
+        let format::Model { version, args, dictionary, input, output, .. } = model;
+
+        let dict = Dictionary::new(dictionary, &args);
+        let tree = if args.loss == Loss::HierarchicalSoftmax {
+            if args.model == ModelKind::Supervised {
+                build_tree(&dict.counts(EntryType::Label))
+            } else {
+                build_tree(&dict.counts(EntryType::Word))
+            }
+        } else {
+            vec![]
+        };
+
+        Model {
+            version,
+            quant: input.is_quantized(),
+            args,
+            dict,
+            wi: input,
+            input: None,
+            wo: output,
+            tree,
+        }
+    }
+
+    pub(crate) fn version(&self) -> i32 {
+        self.version
+    }
+
+    pub(crate) fn args(&self) -> &Args {
+        &self.args
+    }
+
+    pub(crate) fn is_quantized(&self) -> bool {
+        self.quant
+    }
+
+    pub(crate) fn dictionary(&self) -> &Dictionary {
+        &self.dict
+    }
+
+
+    pub(crate) fn dimension(&self) -> usize {
+        self.args.dim as usize
+    }
+
+    pub(crate) fn is_supervised(&self) -> bool {
+        self.args.model == ModelKind::Supervised
+    }
+
+    pub(crate) fn input_matrix(&self) -> &Matrix {
+        match self.input {
+            Some(ref input) if !self.quant => input,
+            _ => &self.wi,
+        }
+    }
+
+    /// `FastText::getWordVector`
+    pub(crate) fn word_vector(&self, word: &str) -> Vec<f32> {
+        let mut vec = vec![0f32; self.dimension()];
+        self.word_vector_into(word, &mut vec);
+
+        vec
+    }
+
+    pub(crate) fn word_vector_into(&self, word: &str, vec: &mut [f32]) {
+        for v in vec.iter_mut() {
+            *v = 0.0;
+        }
+
+        let ngrams = self.dict.subwords(word);
+        for &id in &ngrams {
+            add_row(self.input_matrix(), vec, id as usize);
+        }
+
+        if !ngrams.is_empty() {
+            scale(vec, 1.0 / ngrams.len() as f64);
+        }
+    }
+
+    /// `FastText::ngramVectors`: the vector of each subword of a word.
+    pub(crate) fn ngram_vectors(&self, word: &str) -> Vec<(String, Vec<f32>)> {
+        self.dict.subword_strings(word).into_iter()
+            .map(|(id, ngram)| {
+                let mut vec = vec![0f32; self.dimension()];
+                add_row(self.input_matrix(), &mut vec, id as usize);
+
+                (ngram, vec)
+            })
+            .collect()
+    }
+
+    /// `FastText::getSentenceVector`
+    pub(crate) fn sentence_vector(&self, text: &str) -> Vec<f32> {
+        self.sentence_vector_with(text, self.is_supervised())
+    }
+
+    /// The supervised or the unsupervised branch of `FastText::getSentenceVector`.
+    pub(crate) fn sentence_vector_with(&self, text: &str, supervised: bool) -> Vec<f32> {
+        let mut svec = vec![0f32; self.dimension()];
+        self.sentence_vector_into(text, supervised, &mut svec);
+
+        svec
+    }
+
+    pub(crate) fn sentence_vector_into(&self, text: &str, supervised: bool, svec: &mut [f32]) {
+        for v in svec.iter_mut() {
+            *v = 0.0;
+        }
+
+        if supervised {
+            let (line, _) = self.dict.line(text);
+
+            for &id in &line {
+                add_row(self.input_matrix(), svec, id as usize);
+            }
+
+            if !line.is_empty() {
+                scale(svec, 1.0 / line.len() as f64);
+            }
+        } else {
+            let sentence = text.split('\n').next().unwrap_or_default();
+            let mut vec = vec![0f32; self.dimension()];
+            let mut count = 0;
+
+            for word in sentence.as_bytes().split(|&c| is_space(c)).filter(|w| !w.is_empty()) {
+                let word = String::from_utf8_lossy(word);
+                self.word_vector_into(&word, &mut vec);
+
+                let norm = norm(&vec);
+                if norm > 0.0 {
+                    scale(&mut vec, 1.0 / norm as f64);
+                    for (s, v) in svec.iter_mut().zip(&vec) {
+                        *s += *v;
+                    }
+                    count += 1;
+                }
+            }
+
+            if count > 0 {
+                scale(svec, 1.0 / count as f64);
+            }
+        }
+    }
+
+    /// `FastText::predict`: the `k` most probable labels with their log-probabilities.
+    pub(crate) fn predict(&self, text: &str, k: i32, threshold: f32) -> Result<Vec<(f32, &str)>, String> {
+        let (words, _) = self.dict.line(text);
+        if words.is_empty() {
+            return Ok(vec![]);
+        }
+
+        if k <= 0 {
+            return Err(String::from(K_ERROR));
+        }
+        if self.args.model != ModelKind::Supervised {
+            return Err(String::from(MODEL_ERROR));
+        }
+
+        let hidden = self.hidden(&words);
+        let mut heap = Vec::with_capacity(k as usize + 1);
+
+        if self.args.loss == Loss::HierarchicalSoftmax {
+            let root = 2 * self.wo.rows() as i32 - 2;
+            self.dfs(k as usize, threshold, root, 0.0, &mut heap, &hidden);
+        } else {
+            self.find_k_best(k as usize, threshold, &mut heap, &hidden);
+        }
+
+        cxx::sort_heap(&mut heap, &compare_pairs);
+
+        Ok(heap.into_iter()
+            .map(|(prob, lid)| (prob, self.dict.label(lid)))
+            .collect())
+    }
+
+    fn hidden(&self, input: &[i32]) -> Vec<f32> {
+        let mut hidden = vec![0f32; self.dimension()];
+
+        for &id in input {
+            add_row(&self.wi, &mut hidden, id as usize);
+        }
+        scale(&mut hidden, 1.0 / input.len() as f64);
+
+        hidden
+    }
+
+    fn output(&self, hidden: &[f32]) -> Vec<f32> {
+        let mut output: Vec<f32> = (0..self.wo.rows())
+            .map(|i| dot_row(&self.wo, hidden, i))
+            .collect();
+
+        if self.args.loss == Loss::OneVsAll {
+            for o in output.iter_mut() {
+                *o = sigmoid(*o);
+            }
+        } else if !output.is_empty() {
+            let max = output.iter().fold(output[0], |max, &o| if o < max { max } else { o });
+
+            let mut z = 0f32;
+            for o in output.iter_mut() {
+                *o = (*o - max).exp();
+                z += *o;
+            }
+            for o in output.iter_mut() {
+                *o /= z;
+            }
+        }
+
+        output
+    }
+
+    fn push(heap: &mut Vec<(f32, i32)>, k: usize, pair: (f32, i32)) {
+        heap.push(pair);
+        cxx::push_heap(heap, &compare_pairs);
+
+        if heap.len() > k {
+            cxx::pop_heap(heap, &compare_pairs);
+            heap.pop();
+        }
+    }
+
+    fn find_k_best(&self, k: usize, threshold: f32, heap: &mut Vec<(f32, i32)>, hidden: &[f32]) {
+        for (i, &o) in self.output(hidden).iter().enumerate() {
+            if o < threshold {
+                continue;
+            }
+            if heap.len() == k && std_log(o) < heap[0].0 {
+                continue;
+            }
+
+            Model::push(heap, k, (std_log(o), i as i32));
+        }
+    }
+
+    fn dfs(&self, k: usize, threshold: f32, node: i32, score: f32, heap: &mut Vec<(f32, i32)>, hidden: &[f32]) {
+        if node < 0 || score < std_log(threshold) {
+            return;
+        }
+        if heap.len() == k && score < heap[0].0 {
+            return;
+        }
+
+        let tree_node = self.tree[node as usize];
+        if tree_node.left == -1 && tree_node.right == -1 {
+            Model::push(heap, k, (score, node));
+            return;
+        }
+
+        let f = dot_row(&self.wo, hidden, node as usize - self.wo.rows());
+        let f = (1.0 / (1.0 + (-f).exp()) as f64) as f32;
+
+        self.dfs(k, threshold, tree_node.left, score + std_log((1.0 - f as f64) as f32), heap, hidden);
+        self.dfs(k, threshold, tree_node.right, score + std_log(f), heap, hidden);
+    }
+
+    /// `FastText::loadVectors`: reads a `.vec` file into the dictionary and a new input matrix.
+    ///
+    /// As in the C++ version the dictionary is re-sorted and every row that is not a word of the
+    /// file gets a fresh uniform initialization, while prediction keeps the loaded input matrix.
+    pub(crate) fn load_vectors<R: Read>(&mut self, reader: &mut R) -> Result<(), String> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).map_err(|err| err.to_string())?;
+
+        let mut tokens = content.split(|&c| is_space(c)).filter(|t| !t.is_empty());
+        let mut next = || tokens.next()
+            .map(String::from_utf8_lossy)
+            .ok_or_else(|| String::from("unexpected end of vectors"));
+
+        let n: usize = next()?.parse().map_err(|_| String::from("wrong number of vectors"))?;
+        let dim: usize = next()?.parse().map_err(|_| String::from("wrong dimension"))?;
+        if dim != self.dimension() {
+            return Err(format!("dimension of pretrained vectors {} does not match dimension {}", dim, self.dimension()));
+        }
+
+        let mut words = Vec::with_capacity(n);
+        let mut vectors = DenseMatrix {
+            rows: n,
+            cols: dim,
+            data: Vec::with_capacity(n * dim),
+        };
+        for _ in 0..n {
+            let word = next()?.into_owned();
+            for _ in 0..dim {
+                let v = next()?.parse::<f32>().map_err(|_| format!("wrong vector of {:?}", word))?;
+                vectors.data.push(v);
+            }
+
+            self.dict.add(&word);
+            words.push(word);
+        }
+
+        self.dict.threshold(1, 0);
+
+        let nwords = self.dict.nwords() as usize;
+        let rows = nwords + self.args.bucket as usize;
+        let a = (1.0 / self.args.dim as f64) as f32 as f64;
+        let mut rng = MinstdRand::new(1);
+
+        let mut input = DenseMatrix {
+            rows,
+            cols: dim,
+            data: (0..rows * dim).map(|_| cxx::uniform(&mut rng, -a, a) as f32).collect(),
+        };
+
+        for (i, word) in words.iter().enumerate() {
+            match self.dict.id(word) {
+                Some(idx) if (idx as usize) < nwords => {
+                    let idx = idx as usize;
+                    input.data[idx * dim..(idx + 1) * dim].copy_from_slice(vectors.row(i));
+                }
+                _ => continue,
+            }
+        }
+
+        self.input = Some(Matrix::Dense(input));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_build_tree() {
+        let tree = build_tree(&[5, 3, 1]);
+
+        assert_eq!(tree.len(), 5);
+        assert_eq!((tree[3].left, tree[3].right, tree[3].count), (2, 1, 4));
+        assert_eq!((tree[4].left, tree[4].right, tree[4].count), (3, 0, 9));
+    }
+
+    #[test]
+    fn test_sigmoid() {
+        assert_eq!(sigmoid(-10.0), 0.0);
+        assert_eq!(sigmoid(10.0), 1.0);
+        assert!((sigmoid(0.0) - 0.5).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,37 @@
+use format::EntryType;
+use native;
+
+#[derive(Debug)]
+pub struct Dictionary(*const native::Dictionary);
+
+impl Dictionary {
+    pub(crate) fn new(dict: Option<&native::Dictionary>) -> Dictionary {
+        Dictionary(dict.map(|dict| dict as *const native::Dictionary).unwrap_or(::std::ptr::null()))
+    }
+
+    fn dict(&self) -> Option<&native::Dictionary> {
+        unsafe { self.0.as_ref() }
+    }
+
+    pub fn word_index(&self, word: &str) -> Option<i64> {
+        self.dict()?.id(word).map(|index| index as i64)
+    }
+
+    pub fn get_word(&self, index: i64) -> Option<String> {
+        self.dict()?.word(index as i32).map(String::from)
+    }
+
+    pub fn words_count(&self) -> i32 {
+        self.dict().map(|dict| dict.nwords()).unwrap_or_default()
+    }
+
+    /// The number of occurrences of each word in the training data, by word index.
+    pub fn word_counts(&self) -> Vec<i64> {
+        self.dict().map(|dict| dict.counts(EntryType::Word)).unwrap_or_default()
+    }
+
+    /// The number of tokens in the training data.
+    pub fn tokens_count(&self) -> i64 {
+        self.dict().map(|dict| dict.ntokens()).unwrap_or_default()
+    }
+}
@@ -0,0 +1,175 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use std::str;
+
+use dictionary::Dictionary;
+use format;
+use info::ModelInfo;
+use native::Model;
+use predict::{Predict, PredictRecord};
+use sentence::SentenceMode;
+use vector::Vector;
+
+pub use result::{Err, ResSuccess, RES_OK};
+
+fn to_err(err: format::Error) -> Err {
+    match err {
+        format::Error::Io(ref err) if err.kind() != io::ErrorKind::UnexpectedEof => Err::ResErrorNotOpen,
+        _ => Err::RerErrorWrongModel,
+    }
+}
+
+/// The pure-Rust counterpart of the C++ wrapper.
+#[derive(Default)]
+pub(crate) struct FastTextWrapper(Option<Box<Model>>);
+
+impl FastTextWrapper {
+    pub(crate) fn load_model(&mut self, model_path: &Path) -> Result<ResSuccess, Err> {
+        let file = File::open(model_path).map_err(|_| Err::ResErrorNotOpen)?;
+
+        self.load_model_from_reader(&mut BufReader::new(file))
+    }
+
+    /// There is no mapping in the pure-Rust backend: the model is read into memory.
+    pub(crate) fn load_model_mapped(&mut self, model_path: &Path) -> Result<ResSuccess, Err> {
+        self.load_model(model_path)
+    }
+
+    pub(crate) fn load_model_from_reader<R: Read>(&mut self, reader: &mut R) -> Result<ResSuccess, Err> {
+        let model = format::Model::read(reader).map_err(to_err)?;
+        self.0 = Some(Box::new(Model::new(model)));
+
+        Ok(RES_OK)
+    }
+
+    pub(crate) fn load_vectors(&mut self, vectors_path: &Path) -> Result<ResSuccess, Err> {
+        let model = match self.0 {
+            Some(ref mut model) => model,
+            None => return Err(Err::ResErrorModelNotInit),
+        };
+
+        let file = File::open(vectors_path).map_err(|_| Err::ResErrorNotOpen)?;
+        match model.load_vectors(&mut BufReader::new(file)) {
+            Ok(_) => Ok(RES_OK),
+            Err(_) => Err(Err::RerErrorWrongModel),
+        }
+    }
+
+    pub(crate) fn get_dictionary(&self) -> Dictionary {
+        Dictionary::new(self.0.as_ref().map(|model| model.dictionary()))
+    }
+
+    pub(crate) fn word_to_vector(&self, word: &str) -> Option<Vector> {
+        let vec = Vector::new(self.0.as_ref()?.word_vector(word));
+
+        if !vec.is_empty() {
+            Some(vec)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn sentence_to_vector(&self, text: &str) -> Option<Vector> {
+        let vec = Vector::new(self.0.as_ref()?.sentence_vector(text));
+
+        if !vec.is_empty() {
+            Some(vec)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn model_info(&self) -> Result<ModelInfo, Err> {
+        let model = self.0.as_ref().ok_or(Err::ResErrorModelNotInit)?;
+
+        Ok(ModelInfo {
+            version: model.version(),
+            nwords: model.dictionary().nwords(),
+            nlabels: model.dictionary().nlabels(),
+            ntokens: model.dictionary().ntokens(),
+            quantized: model.is_quantized(),
+            args: model.args().clone(),
+        })
+    }
+
+    pub(crate) fn dimension(&self) -> usize {
+        self.0.as_ref().map(|model| model.dimension()).unwrap_or_default()
+    }
+
+    pub(crate) fn word_vector_into(&self, word: &str, out: &mut [f32]) -> Result<ResSuccess, Err> {
+        let model = self.0.as_ref().ok_or(Err::ResErrorModelNotInit)?;
+        if out.len() != model.dimension() {
+            return Err(Err::ResErrorWrongDimension);
+        }
+
+        model.word_vector_into(word, out);
+
+        Ok(RES_OK)
+    }
+
+    pub(crate) fn sentence_vector_into(&self, text: &str, out: &mut [f32]) -> Result<ResSuccess, Err> {
+        let model = self.0.as_ref().ok_or(Err::ResErrorModelNotInit)?;
+        if out.len() != model.dimension() {
+            return Err(Err::ResErrorWrongDimension);
+        }
+
+        model.sentence_vector_into(text, model.is_supervised(), out);
+
+        Ok(RES_OK)
+    }
+
+    /// Writes the vectors of `words` into the rows of `out`; the native backend embeds sequentially.
+    pub(crate) fn embed_words(&self, words: &[&str], out: &mut [f32], _threads: usize) -> Result<ResSuccess, Err> {
+        let model = self.0.as_ref().ok_or(Err::ResErrorModelNotInit)?;
+        for (word, row) in words.iter().zip(out.chunks_mut(model.dimension())) {
+            model.word_vector_into(word, row);
+        }
+
+        Ok(RES_OK)
+    }
+
+    /// Writes the vectors of `texts` into the rows of `out`; the native backend embeds sequentially.
+    pub(crate) fn embed_sentences(&self, texts: &[&str], out: &mut [f32], _threads: usize) -> Result<ResSuccess, Err> {
+        let model = self.0.as_ref().ok_or(Err::ResErrorModelNotInit)?;
+        for (text, row) in texts.iter().zip(out.chunks_mut(model.dimension())) {
+            model.sentence_vector_into(text, model.is_supervised(), row);
+        }
+
+        Ok(RES_OK)
+    }
+
+    pub(crate) fn sentence_to_vector_with(&self, text: &str, mode: SentenceMode) -> Option<Vector> {
+        let supervised = mode == SentenceMode::Supervised;
+        let vec = Vector::new(self.0.as_ref()?.sentence_vector_with(text, supervised));
+
+        if !vec.is_empty() {
+            Some(vec)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn predict(&self, text: &str, count: i32) -> Result<Predict, String> {
+        let model = match self.0 {
+            Some(ref model) => model,
+            None => return Err(String::from("model is not loaded")),
+        };
+
+        model.predict(text, count, 0.0)
+            .map(|records| Predict::new(records.into_iter()
+                .map(|(prediction, word)| PredictRecord::new(prediction, word))
+                .collect()))
+    }
+
+    pub(crate) fn predict_bytes(&self, text: &mut Vec<u8>, count: i32) -> Result<Predict, String> {
+        match str::from_utf8(text) {
+            Ok(text) => self.predict(text, count),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    pub(crate) fn ngram_vectors(&self, word: &str) -> Vec<(String, Vec<f32>)> {
+        self.0.as_ref().map_or(vec![], |model| model.ngram_vectors(word))
+    }
+}
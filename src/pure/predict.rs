@@ -0,0 +1,54 @@
+const EPS: f32 = 1e-6;
+
+#[derive(Debug)]
+pub struct PredictRecord<'a>(f32, &'a str);
+
+impl<'a> PredictRecord<'a> {
+    pub(crate) fn new(prediction: f32, word: &'a str) -> PredictRecord<'a> {
+        PredictRecord(prediction, word)
+    }
+
+    pub fn prediction(&self) -> f32 {
+        self.0
+    }
+
+    pub fn word(&self) -> &'a str {
+        self.1
+    }
+}
+
+impl<'a> PartialEq for PredictRecord<'a> {
+    fn eq(&self, other: &PredictRecord) -> bool {
+        (self.0 - other.0).abs() < EPS && self.1 == other.1
+    }
+}
+
+#[derive(Debug)]
+pub struct Predict<'a> {
+    data: Vec<PredictRecord<'a>>,
+}
+
+impl<'a> Predict<'a> {
+    pub(crate) fn new(data: Vec<PredictRecord<'a>>) -> Predict<'a> {
+        Predict {
+            data,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[PredictRecord<'a>] {
+        self.data.as_slice()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Errors are reported by `FastText` before a `Predict` is built.
+    pub fn err(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use std::marker::PhantomData;
+
+pub struct Vector<'a> {
+    data: Vec<f32>,
+    _marker: PhantomData<&'a [f32]>,
+}
+
+impl<'a> Vector<'a> {
+    pub(crate) fn new(data: Vec<f32>) -> Vector<'a> {
+        Vector {
+            data,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        self.data.as_slice()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
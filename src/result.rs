@@ -0,0 +1,13 @@
+pub const RES_OK: ResSuccess = ResSuccess(0);
+
+#[derive(Debug, Clone, Copy)]
+pub struct ResSuccess(i32);
+
+#[derive(Debug, Clone, Copy)]
+pub enum Err {
+    ResErrorNotOpen,
+    RerErrorWrongModel,
+    ResErrorModelNotInit,
+    ResErrorExecution,
+    ResErrorWrongDimension,
+}
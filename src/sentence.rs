@@ -0,0 +1,235 @@
+//! Sentence embeddings.
+
+use std::collections::HashMap;
+
+use {Err, FastText};
+
+/// The way fastText averages the words of a sentence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SentenceMode {
+    /// The average of the input rows of the words, their character n-grams and the word n-grams
+    /// of the sentence, as supervised models compute it.
+    Supervised,
+    /// The average of the L2-normalized vectors of the words, as unsupervised models compute it.
+    Unsupervised,
+}
+
+impl FastText {
+    /// The sentence vector with the semantics of the kind of the loaded model.
+    pub fn sentence_vector(&self, text: &str) -> Option<Vec<f32>> {
+        self.0.sentence_to_vector(text)
+            .map(|vector| vector.as_slice().to_owned())
+    }
+
+    /// Writes the sentence vector of `text` into `out`, which must be of the model dimension.
+    ///
+    /// Unlike `sentence_vector` it doesn't allocate a vector per call.
+    pub fn sentence_vector_into(&self, text: &str, out: &mut [f32]) -> Result<(), Err> {
+        self.0.sentence_vector_into(text, out).map(|_| ())
+    }
+
+    /// The sentence vector with the given semantics whatever the kind of the loaded model is.
+    pub fn sentence_vector_with(&self, text: &str, mode: SentenceMode) -> Option<Vec<f32>> {
+        self.0.sentence_to_vector_with(text, mode)
+            .map(|vector| vector.as_slice().to_owned())
+    }
+}
+
+/// How a `SentenceEncoder` pools the word vectors of a sentence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pooling {
+    /// The average of the word vectors.
+    Mean,
+    /// The element-wise maximum of the word vectors.
+    Max,
+    /// Smooth inverse frequency: the average of the word vectors weighted by `a / (a + p(w))`,
+    /// `p(w)` being the frequency of the word in the training data. Once the encoder is fitted
+    /// the projection on the first principal component of the fitted sentences is removed.
+    Sif { a: f32 },
+    /// The weighted average of the word vectors, the weight of a word being its count in the
+    /// sentence times its inverse frequency `ln((1 + ntokens) / (1 + count(w))) + 1` in the training data.
+    TfIdf,
+}
+
+const POWER_ITERATIONS: usize = 100;
+
+/// Sentence embeddings pooled from the word vectors of a loaded model.
+///
+/// Sentences are split on whitespace; words out of the vocabulary get their vector from
+/// character n-grams and are weighted as if they never occurred in the training data.
+pub struct SentenceEncoder<'a> {
+    model: &'a FastText,
+    pooling: Pooling,
+    /// The count of each word of the vocabulary in the training data.
+    counts: HashMap<String, i64>,
+    ntokens: i64,
+    component: Option<Vec<f32>>,
+}
+
+impl<'a> SentenceEncoder<'a> {
+    pub fn new(model: &'a FastText, pooling: Pooling) -> SentenceEncoder<'a> {
+        let dict = model.0.get_dictionary();
+        let counts = dict.word_counts()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, count)| dict.get_word(i as i64).map(|word| (word, count)))
+            .collect();
+
+        SentenceEncoder {
+            model,
+            pooling,
+            counts,
+            ntokens: dict.tokens_count(),
+            component: None,
+        }
+    }
+
+    pub fn pooling(&self) -> Pooling {
+        self.pooling
+    }
+
+    /// The first principal component removed from SIF embeddings, once fitted.
+    pub fn component(&self) -> Option<&[f32]> {
+        self.component.as_ref().map(|component| component.as_slice())
+    }
+
+    /// Computes the first principal component of the SIF embeddings of `sentences`.
+    ///
+    /// It only affects the `Sif` pooling; sentences without words are ignored.
+    pub fn fit<I, S>(&mut self, sentences: I)
+        where I: IntoIterator<Item = S>,
+              S: AsRef<str>
+    {
+        if let Pooling::Sif { .. } = self.pooling {
+            let embeddings: Vec<Vec<f32>> = sentences.into_iter()
+                .filter_map(|sentence| self.pool(sentence.as_ref()))
+                .collect();
+
+            self.component = first_component(&embeddings);
+        }
+    }
+
+    /// The embedding of `text`, or `None` if it has no words.
+    pub fn encode(&self, text: &str) -> Option<Vec<f32>> {
+        let mut embedding = self.pool(text)?;
+
+        if let Some(ref component) = self.component {
+            let projection = dot(&embedding, component);
+            for (e, c) in embedding.iter_mut().zip(component) {
+                *e -= projection * c;
+            }
+        }
+
+        Some(embedding)
+    }
+
+    fn pool(&self, text: &str) -> Option<Vec<f32>> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut pooled: Option<Vec<f32>> = None;
+        let mut total = 0f32;
+
+        for word in &words {
+            let vector = self.model.0.word_to_vector(word)?;
+            let weight = self.weight(word);
+
+            let pooled = pooled.get_or_insert_with(|| match self.pooling {
+                Pooling::Max => vec![::std::f32::NEG_INFINITY; vector.len()],
+                _ => vec![0f32; vector.len()],
+            });
+            for (p, v) in pooled.iter_mut().zip(vector.as_slice()) {
+                match self.pooling {
+                    Pooling::Max => *p = p.max(*v),
+                    _ => *p += v * weight,
+                }
+            }
+
+            total += weight;
+        }
+
+        let divisor = match self.pooling {
+            Pooling::Max => 1.0,
+            Pooling::Sif { .. } => words.len() as f32,
+            Pooling::Mean | Pooling::TfIdf => total,
+        };
+
+        pooled.map(|mut pooled| {
+            if divisor > 0.0 {
+                for p in pooled.iter_mut() {
+                    *p /= divisor;
+                }
+            }
+
+            pooled
+        })
+    }
+
+    fn count(&self, word: &str) -> i64 {
+        self.counts.get(word).cloned().unwrap_or_default()
+    }
+
+    /// The weight of an occurrence of `word` in a sentence.
+    fn weight(&self, word: &str) -> f32 {
+        match self.pooling {
+            Pooling::Mean | Pooling::Max => 1.0,
+            Pooling::Sif { a } => {
+                let frequency = if self.ntokens > 0 {
+                    self.count(word) as f32 / self.ntokens as f32
+                } else {
+                    0.0
+                };
+
+                a / (a + frequency)
+            }
+            Pooling::TfIdf => ((1 + self.ntokens) as f32 / (1 + self.count(word)) as f32).ln() + 1.0,
+        }
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// The first right singular vector of the matrix whose rows are `rows`, by power iteration.
+fn first_component(rows: &[Vec<f32>]) -> Option<Vec<f32>> {
+    let dim = rows.first()?.len();
+    let mut component: Vec<f32> = (0..dim).map(|i| 1.0 + i as f32 / dim as f32).collect();
+
+    for _ in 0..POWER_ITERATIONS {
+        let mut next = vec![0f32; dim];
+        for row in rows {
+            let projection = dot(row, &component);
+            for (n, r) in next.iter_mut().zip(row) {
+                *n += projection * r;
+            }
+        }
+
+        let norm = dot(&next, &next).sqrt();
+        if norm == 0.0 {
+            return None;
+        }
+        for n in next.iter_mut() {
+            *n /= norm;
+        }
+
+        component = next;
+    }
+
+    Some(component)
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_first_component() {
+        let rows = vec![vec![2.0, 1.0], vec![-2.0, -1.0], vec![4.0, 2.0]];
+
+        let component = first_component(&rows).unwrap();
+        let expected = [2.0 / 5f32.sqrt(), 1.0 / 5f32.sqrt()];
+
+        assert!((component[0] - expected[0]).abs() < 1e-5 && (component[1] - expected[1]).abs() < 1e-5,
+                "check component {:?}", component);
+        assert_eq!(first_component(&[]), None);
+    }
+}
@@ -1,13 +1,25 @@
 use fasttext::*;
 use FastText;
+use ann::{self, ExactIndex, HnswParams, VocabularyIndex};
+use embedding::Embedding;
+use format::{self, ModelKind};
 use predict::PredictRecord;
+use sentence::{Pooling, SentenceEncoder, SentenceMode};
+use testing::fixtures::{golden_model, missing, model_bytes, model_path, scratch_path, stem, vectors_path, SUPERVISED, UNSUPERVISED};
+#[cfg(not(feature = "pure-rust"))]
+use train::{PretrainedVectors, QuantizeArgs, TrainArgs};
+use word2vec::Word2Vec;
+use wordvector_base::WordVectorModel;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
 use std::path::Path;
+#[cfg(not(feature = "pure-rust"))]
+use std::sync::Arc;
 
 static UNKNOWN_PATH: &'static str = "unknown path";
-static UNSUPERVISED_MODEL_PATH: &'static str = "./test-data/unsupervised_model.bin";
-static UNSUPERVISED_VECTORS_PATH: &'static str = "./test-data/unsupervised_model.vec";
-static SUPERVISED_MODEL_PATH: &'static str = "./test-data/supervised_model.bin";
-static SUPERVISED_VECTORS_PATH: &'static str = "./test-data/supervised_model.vec";
+#[cfg(not(feature = "pure-rust"))]
+static UNSUPERVISED_TEXT_PATH: &'static str = "./unsupervised_text.txt";
+static SUPERVISED_TEXT_PATH: &'static str = "./supervised_text.txt";
 
 fn path(p: &str) -> &Path {
     Path::new(p)
@@ -18,8 +30,19 @@ fn test_fasttext_default() {
     FastText::default();
 }
 
+#[test]
+fn test_fasttext_send() {
+    fn assert_send<T: Send>() {}
+
+    assert_send::<FastText>();
+}
+
 #[test]
 fn test_fasttext_load_model() {
+    if missing() {
+        return;
+    }
+
     let mut model = FastTextWrapper::default();
 
     match model.load_model(path(UNKNOWN_PATH)) {
@@ -27,20 +50,133 @@ fn test_fasttext_load_model() {
         Err(_) => assert!(true),
     }
 
-    match model.load_model(path(UNSUPERVISED_MODEL_PATH)) {
+    match model.load_model(&model_path(UNSUPERVISED)) {
         Ok(_) => assert!(true),
         Err(err) => assert!(false, err),
     }
 }
 
+#[test]
+fn test_fasttext_from_reader() {
+    if missing() {
+        return;
+    }
+
+    match FastText::from_reader(File::open(model_path(UNSUPERVISED)).unwrap()) {
+        Ok(model) => assert_eq!(model.word_index("златом"), Some(22), "check model working"),
+        Err(err) => assert!(false, "failed to load model {:?} from reader with error {:?}", model_path(UNSUPERVISED), err),
+    }
+
+    match FastText::from_reader(File::open(vectors_path(UNSUPERVISED)).unwrap()) {
+        Ok(_) => assert!(false, "failed to raise an error for a non-binary model"),
+        Err(_) => assert!(true),
+    }
+}
+
+#[test]
+fn test_fasttext_from_bytes() {
+    if missing() {
+        return;
+    }
+
+    let bytes = fs::read(model_path(SUPERVISED)).unwrap();
+
+    match FastText::from_bytes(bytes.as_slice()) {
+        Ok(model) => assert!(model.word_index("вечер").is_some(), "check model working"),
+        Err(err) => assert!(false, "failed to load model {:?} from bytes with error {:?}", model_path(SUPERVISED), err),
+    }
+
+    match FastText::from_bytes(&bytes[..bytes.len() / 2]) {
+        Ok(_) => assert!(false, "failed to raise an error for a truncated model"),
+        Err(_) => assert!(true),
+    }
+}
+
+#[test]
+fn test_fasttext_with_mapped_model() {
+    if missing() {
+        return;
+    }
+
+    match FastText::with_mapped_model(path(UNKNOWN_PATH)) {
+        Ok(_) => assert!(false, "failed to raise an error for an unknown model path"),
+        Err(_) => assert!(true),
+    }
+
+    let mapped = match FastText::with_mapped_model(&model_path(UNSUPERVISED)) {
+        Ok(model) => model,
+        Err(err) => panic!("failed to map model {:?} with error {:?}", model_path(UNSUPERVISED), err),
+    };
+    let loaded = FastText::from_reader(File::open(model_path(UNSUPERVISED)).unwrap()).unwrap();
+
+    assert_eq!(mapped.word_index("златом"), Some(22), "check model working");
+    assert_eq!(
+        mapped.word_to_vector("златом"),
+        loaded.word_to_vector("златом"),
+        "check mapped vector"
+    );
+}
+
+#[test]
+fn test_fasttext_mapped_golden_model() {
+    // the input matrix of the golden model starts at offset 131, not aligned on 4 bytes
+    let path = scratch_path("mapped_golden_model.bin");
+    fs::write(&path, golden_model()).unwrap();
+    let mapped = FastText::with_mapped_model(&path);
+    fs::remove_file(&path).unwrap();
+    match mapped {
+        Ok(model) => assert_close(&[0.5, -0.5, 0.25, 0.0], &model.word_to_vector("дуб").unwrap(), "mapped vector"),
+        Err(err) => assert!(false, "failed to map the golden model with error {:?}", err),
+    }
+
+    // 2^62 rows of 4 floats wrap around to 0 bytes
+    let mut bytes = golden_model();
+    bytes[115..123].copy_from_slice(&(1i64 << 62).to_le_bytes());
+    fs::write(&path, bytes).unwrap();
+    let mapped = FastText::with_mapped_model(&path);
+    fs::remove_file(&path).unwrap();
+    match mapped {
+        Ok(_) => assert!(false, "mapped a matrix larger than the file"),
+        Err(_) => assert!(true),
+    }
+}
+
+#[test]
+fn test_fasttext_word2vec() {
+    if missing() {
+        return;
+    }
+
+    let model = FastText::with_model(&stem(UNSUPERVISED)).unwrap();
+
+    let mut binary = Vec::new();
+    model.save_word2vec_binary(&mut binary).unwrap();
+    let mut text = Vec::new();
+    model.save_word2vec_text(&mut text).unwrap();
+
+    for imported in vec![Word2Vec::from_binary(binary.as_slice()), Word2Vec::from_text(text.as_slice())] {
+        match imported {
+            Ok(imported) => {
+                assert_eq!(imported.word_index("златом"), Some(22), "check vocabulary");
+                assert_eq!(imported.word_to_vector("златом"), model.word_to_vector("златом"), "check vectors");
+            }
+            Err(err) => assert!(false, "failed to import word2vec vectors with error {}", err),
+        }
+    }
+}
+
 #[test]
 fn test_fasttext_load_vectors() {
+    if missing() {
+        return;
+    }
+
     let mut model = FastTextWrapper::default();
 
-    match model.load_model(path(UNSUPERVISED_MODEL_PATH)) {
+    match model.load_model(&model_path(UNSUPERVISED)) {
         Ok(_) => assert!(true),
         Err(err) => {
-            println!("Failed to load model {:?} with error {:?}", UNSUPERVISED_MODEL_PATH, err);
+            println!("Failed to load model {:?} with error {:?}", model_path(UNSUPERVISED), err);
             assert!(false)
         }
     }
@@ -53,10 +189,10 @@ fn test_fasttext_load_vectors() {
         Err(_) => assert!(true),
     }
 
-    match model.load_vectors(path(UNSUPERVISED_VECTORS_PATH)) {
+    match model.load_vectors(&vectors_path(UNSUPERVISED)) {
         Ok(_) => assert!(true),
         Err(err) => {
-            println!("Failed to load vectors {:?} with error {:?}", UNSUPERVISED_VECTORS_PATH, err);
+            println!("Failed to load vectors {:?} with error {:?}", vectors_path(UNSUPERVISED), err);
             assert!(false)
         }
     }
@@ -64,20 +200,24 @@ fn test_fasttext_load_vectors() {
 
 #[test]
 fn test_fasttext_get_dictionary() {
+    if missing() {
+        return;
+    }
+
     let mut model = FastTextWrapper::default();
 
-    match model.load_model(path(UNSUPERVISED_MODEL_PATH)) {
+    match model.load_model(&model_path(UNSUPERVISED)) {
         Ok(_) => assert!(true),
         Err(err) => {
-            println!("Failed to load model {:?} with error {:?}", UNSUPERVISED_MODEL_PATH, err);
+            println!("Failed to load model {:?} with error {:?}", model_path(UNSUPERVISED), err);
             assert!(false)
         }
     }
 
-    match model.load_vectors(path(UNSUPERVISED_VECTORS_PATH)) {
+    match model.load_vectors(&vectors_path(UNSUPERVISED)) {
         Ok(_) => assert!(true),
         Err(err) => {
-            println!("Failed to load vectors {:?} with error {:?}", UNSUPERVISED_VECTORS_PATH, err);
+            println!("Failed to load vectors {:?} with error {:?}", vectors_path(UNSUPERVISED), err);
             assert!(false)
         }
     }
@@ -112,115 +252,427 @@ fn test_fasttext_get_dictionary() {
 
 #[test]
 fn test_fasttext_get_word_vector() {
-    let mut model = FastTextWrapper::default();
+    let path = scratch_path("golden_model.bin");
+    fs::write(&path, golden_model()).unwrap();
 
-    match model.load_model(path(UNSUPERVISED_MODEL_PATH)) {
+    let mut model = FastTextWrapper::default();
+    let loaded = model.load_model(&path);
+    fs::remove_file(&path).unwrap();
+    match loaded {
         Ok(_) => assert!(true),
-        Err(err) => {
-            println!("Failed to load model {:?} with error {:?}", UNSUPERVISED_MODEL_PATH, err);
-            assert!(false)
-        }
+        Err(err) => assert!(false, "failed to load the golden model with error {:?}", err),
     }
 
-    match model.load_vectors(path(UNSUPERVISED_VECTORS_PATH)) {
-        Ok(_) => assert!(true),
-        Err(err) => {
-            println!("Failed to load vectors {:?} with error {:?}", UNSUPERVISED_VECTORS_PATH, err);
-            assert!(false)
-        }
-    }
+    // the average of the word row and of the second row for its 6 n-grams
+    let word = "златом";
+    let expected_vec = [4.0f32 / 7.0, -1.0 / 7.0, 4.5 / 7.0, 4.0 / 7.0];
+    match model.word_to_vector(word) {
+        Some(vec) => assert_close(&expected_vec, vec.as_slice(), "word vector"),
+        None => assert!(false, "failed to get vector for word {:?}", word),
+    };
 
-    let word = String::from("златом");
-    let expected_vec = vec![
-        -0.00093162473f32, -0.00064210495, -0.0009707032, 0.00031974318, -0.0002315091, 0.002165826, 0.0018783867, 0.0021941606, 0.0014265366, -0.0004747169,
-        0.00042756647, 0.002485942, -3.925598e-05, 0.0019237917, -0.0005152924, 0.00038866568, 0.0009962652, 1.5210654e-05, -0.0002595325, -0.00020531945,
-        0.00032480282, -0.0004953152, 0.0011179452, 0.00047672325, -0.001567682, 0.0011290247, -0.001348938, 0.0013085243, 0.00067846774, -0.0006734705,
-        -5.052518e-05, -0.0015279177, -0.00070518075, 0.00029840754, 0.00085332844, 0.00039165834, 0.0004000477, 0.00023921908, 0.0010118099, -0.00033629616,
-        0.00089929136, 0.0023271097, -0.0025798841, 0.0011896471, 0.0010260209, -0.00046896562, -5.8911148e-05, 0.0013999777, 0.002503657, -0.00042554422,
-        0.0006372212, 0.0020875847, -0.0013018411, 0.00032486717, 0.0005404552, 0.000112594964, 0.0009362643, -0.0021627878, 0.0002152612, -0.00052340183,
-        -0.00093787076, -0.0017059175, 0.00025377644, 0.00017843576, -0.0014451658, -0.0015405576, 0.00087846775, 0.0005363762, 0.00039133723, 0.002590361,
-        0.00010595202, 0.001551432, 0.0008010692, -0.00014902855, -0.0018039232, -0.0013080842, -0.00027898262, 0.0011848757, 0.0003816138, 0.00019682113,
-        0.002425637, 0.0033935579, 0.00022636236, 0.00014700758, -0.00064028014, -0.00031854503, -0.00012771421, 0.00016985959, 0.0013882271, -0.0021626558,
-        0.0010302362, 0.0005801475, -0.0024974225, -0.00091070565, -0.00015146619, -0.0018224111, -0.0011335025, -9.8327204e-05, 0.00093681796, 0.00021071793,
-    ];
-    const COEFF: f32 = 1000.0;
-
-    match model.word_to_vector(word.as_str()) {
-        Some(vec) => assert_eq!(
-            vec.as_slice().iter()
-                .map(|v| (v * COEFF).trunc())
-                .collect::<Vec<f32>>(),
-            expected_vec.iter()
-                .map(|v| (v * COEFF).trunc())
-                .collect::<Vec<f32>>()),
-        None => {
-            println!("failed to get vector for word {:?}", word);
-            assert!(false)
-        }
+    // an unknown word has only its n-grams
+    match model.word_to_vector("дуб") {
+        Some(vec) => assert_close(&[0.5, -0.5, 0.25, 0.0], vec.as_slice(), "unknown word vector"),
+        None => assert!(false, "failed to get vector for an unknown word"),
     };
 }
 
 #[test]
 fn test_fasttext_get_sentence_vector() {
+    if missing() {
+        return;
+    }
+
     let mut model = FastTextWrapper::default();
 
-    match model.load_model(path(UNSUPERVISED_MODEL_PATH)) {
+    match model.load_model(&model_path(UNSUPERVISED)) {
         Ok(_) => assert!(true),
         Err(err) => {
-            println!("Failed to load model {:?} with error {:?}", UNSUPERVISED_MODEL_PATH, err);
+            println!("Failed to load model {:?} with error {:?}", model_path(UNSUPERVISED), err);
             assert!(false)
         }
     }
 
-    match model.load_vectors(path(UNSUPERVISED_VECTORS_PATH)) {
+    match model.load_vectors(&vectors_path(UNSUPERVISED)) {
         Ok(_) => assert!(true),
         Err(err) => {
-            println!("Failed to load vectors {:?} with error {:?}", UNSUPERVISED_VECTORS_PATH, err);
+            println!("Failed to load vectors {:?} with error {:?}", vectors_path(UNSUPERVISED), err);
             assert!(false)
         }
     }
 
     let text = String::from("Кащей над златом чахнет");
-    let expected_vec = vec![
-        0.037130516f32, -0.023536012, -0.05462671, -0.0033913944, -0.018964574, 0.06834176, 0.11407065, 0.16753219, 0.054430895, 0.08919889,
-        0.062001314, 0.12970737, -0.08993994, -0.0013820566, 0.0024787933, 0.015299875, 0.043955993, -0.02965922, -0.002137018, -0.033652093,
-        0.03392063, -0.084329106, 0.07451887, 0.020768367, -0.020052189, 0.009265052, -0.020497253, 0.13506308, 0.027629945, -0.036651403,
-        -0.053276077, 0.00411354, 0.035692926, 0.025573859, 0.015886994, 0.046156306, 0.03847931, -0.013042267, -0.016854543, 0.017981935,
-        0.03060818, 0.009657327, -0.13549992, -0.02106245, 0.018689439, 0.0015525157, 0.0126991235, 0.03377308, 0.045630272, 0.047137648,
-        0.014620029, 0.1009623, -0.05738701, -0.053834576, 0.06026704, -0.011569845, 0.02681889, -0.027683325, -0.0058202855, -0.010907434,
-        -0.060490265, -0.0024461383, 0.10436508, -0.0076349233, 0.019921903, -0.038126707, 0.03616117, 0.0049869185, 0.027135931, 0.026441898,
-        0.027044544, 0.013437899, 0.019479826, 0.051475823, 0.06197407, -0.045852486, -0.016118113, 0.10718948, 0.017485319, -0.02544314,
-        -0.026579026, 0.11026137, 0.064151205, 0.0035600364, -0.0493346, 0.021935625, -0.010017559, 0.04056901, 0.04047651, -0.0039925557,
-        0.029625436, 0.030468367, -0.07004885, -0.028973147, 0.0024994463, -0.032818604, -0.092352696, -0.014490175, 0.07920408, -0.0061761905
-    ];
-    const COEFF: f32 = 1000.0;
+    let expected_vec = normalized_average(text.split_whitespace()
+        .map(|word| model.word_to_vector(word).unwrap().as_slice().to_owned())
+        .collect());
 
     match model.sentence_to_vector(text.as_str()) {
-        Some(vec) => assert_eq!(
-            vec.as_slice().iter()
-                .map(|v| (v * COEFF).trunc())
-                .collect::<Vec<f32>>(),
-            expected_vec.as_slice().iter()
-                .map(|v| (v * COEFF).trunc())
-                .collect::<Vec<f32>>(),
-            "check word vector"
-        ),
+        Some(vec) => assert_close(&expected_vec, vec.as_slice(), "sentence vector"),
         None => assert!(false, "failed to get vector for sentence {:?}", text),
     };
 }
 
+fn normalized_average(vectors: Vec<Vec<f32>>) -> Vec<f32> {
+    let mut sum = vec![0f32; vectors[0].len()];
+    let mut count = 0;
+
+    for vec in vectors {
+        let norm = vec.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            sum.iter_mut().zip(vec).for_each(|(s, v)| *s += v / norm);
+            count += 1;
+        }
+    }
+
+    sum.into_iter().map(|s| s / count as f32).collect()
+}
+
+fn assert_close(expected: &[f32], got: &[f32], msg: &str) {
+    const EPS: f32 = 1e-5;
+
+    assert_eq!(expected.len(), got.len(), "check dimension of {}", msg);
+    assert!(expected.iter().zip(got).all(|(e, g)| (e - g).abs() < EPS), "check {}: {:?} != {:?}", msg, expected, got);
+}
+
+#[test]
+fn test_fasttext_sentence_vector_unsupervised() {
+    if missing() {
+        return;
+    }
+
+    let model = FastText::with_model(&stem(UNSUPERVISED)).unwrap();
+    let text = "Кащей над златом чахнет";
+
+    let expected = model.0.sentence_to_vector(text).unwrap().as_slice().to_owned();
+    assert_eq!(model.sentence_to_vector(text), Some(expected.clone()), "check WordVectorModel sentence vector");
+    assert_eq!(model.sentence_vector(text), Some(expected.clone()), "check model semantics");
+    assert_eq!(model.sentence_vector_with(text, SentenceMode::Unsupervised), Some(expected), "check unsupervised semantics");
+
+    let words = text.split_whitespace().map(|word| model.word_to_vector(word).unwrap()).collect();
+    assert_close(
+        &normalized_average(words),
+        &model.sentence_vector(text).unwrap(),
+        "normalized average of word vectors"
+    );
+
+    assert_ne!(
+        model.sentence_vector_with(text, SentenceMode::Supervised),
+        model.sentence_vector_with(text, SentenceMode::Unsupervised),
+        "check supervised semantics"
+    );
+}
+
+#[test]
+fn test_fasttext_sentence_vector_supervised() {
+    if missing() {
+        return;
+    }
+
+    let model = FastText::with_model(&stem(SUPERVISED)).unwrap();
+    let text = "Добрый вечер";
+
+    let expected = model.0.sentence_to_vector(text).unwrap().as_slice().to_owned();
+    assert_eq!(model.sentence_to_vector(text), Some(expected.clone()), "check WordVectorModel sentence vector");
+    assert_eq!(model.sentence_vector_with(text, SentenceMode::Supervised), Some(expected), "check supervised semantics");
+
+    assert_eq!(
+        model.sentence_vector_with("вечер", SentenceMode::Supervised),
+        model.word_to_vector("вечер"),
+        "check average of input rows"
+    );
+
+    let words = text.split_whitespace().map(|word| model.word_to_vector(word).unwrap()).collect();
+    assert_close(
+        &normalized_average(words),
+        &model.sentence_vector_with(text, SentenceMode::Unsupervised).unwrap(),
+        "unsupervised semantics"
+    );
+}
+
+#[test]
+fn test_sentence_encoder_mean_max() {
+    if missing() {
+        return;
+    }
+
+    let model = FastText::with_model(&stem(UNSUPERVISED)).unwrap();
+    let text = "Кащей над златом чахнет";
+    let words: Vec<Vec<f32>> = text.split_whitespace().map(|word| model.word_to_vector(word).unwrap()).collect();
+
+    let mean: Vec<f32> = (0..words[0].len())
+        .map(|i| words.iter().map(|vec| vec[i]).sum::<f32>() / words.len() as f32)
+        .collect();
+    assert_close(&mean, &SentenceEncoder::new(&model, Pooling::Mean).encode(text).unwrap(), "mean pooling");
+
+    let max: Vec<f32> = (0..words[0].len())
+        .map(|i| words.iter().map(|vec| vec[i]).fold(::std::f32::NEG_INFINITY, f32::max))
+        .collect();
+    assert_eq!(SentenceEncoder::new(&model, Pooling::Max).encode(text), Some(max), "check max pooling");
+
+    assert_eq!(SentenceEncoder::new(&model, Pooling::Mean).encode("  "), None, "check empty sentence");
+}
+
+#[test]
+fn test_sentence_encoder_weighted() {
+    if missing() {
+        return;
+    }
+
+    let model = FastText::with_model(&stem(UNSUPERVISED)).unwrap();
+    let texts = ["Кащей над златом чахнет", "Там царь Кащей над златом чахнет", "там на неведомых дорожках"];
+
+    for pooling in &[Pooling::Sif { a: 1e-3 }, Pooling::TfIdf] {
+        let encoder = SentenceEncoder::new(&model, *pooling);
+
+        let single = encoder.encode("златом").unwrap();
+        let repeated = encoder.encode("златом златом").unwrap();
+        assert_close(&single, &repeated, "weights of a repeated word");
+
+        assert_ne!(encoder.encode(texts[0]), SentenceEncoder::new(&model, Pooling::Mean).encode(texts[0]), "check weights");
+    }
+
+    let mut encoder = SentenceEncoder::new(&model, Pooling::Sif { a: 1e-3 });
+    assert_eq!(encoder.component(), None, "check component before fit");
+
+    encoder.fit(texts.iter());
+    let component = encoder.component().unwrap().to_owned();
+    assert!((component.iter().map(|c| c * c).sum::<f32>() - 1.0).abs() < 1e-4, "check component norm");
+
+    for text in &texts {
+        let embedding = encoder.encode(text).unwrap();
+        let projection: f32 = embedding.iter().zip(&component).map(|(e, c)| e * c).sum();
+        assert!(projection.abs() < 1e-4, "check component removal of {:?}: {}", text, projection);
+    }
+}
+
+#[test]
+fn test_sentence_encoder_weights() {
+    // 8 tokens: p(кот) = 0.75 and p(дуб) = 0.25
+    let words = [("кот", 6), ("дуб", 2)];
+    let input: [&[f32]; 2] = [&[1.0, 0.0], &[0.0, 1.0]];
+    let model = FastText::from_bytes(&model_bytes(&words, 0, &input)).unwrap();
+
+    let encode = |pooling, text| SentenceEncoder::new(&model, pooling).encode(text).unwrap();
+    assert_close(&[1.0 / 3.0, 2.0 / 3.0], &encode(Pooling::Mean, "кот дуб дуб"), "mean");
+    assert_close(&[1.0, 1.0], &encode(Pooling::Max, "кот дуб дуб"), "max");
+    // idf(кот) = ln(9 / 7) + 1 = 1.25131 and idf(дуб) = ln(9 / 3) + 1 = 2.09861
+    assert_close(&[0.229661, 0.770339], &encode(Pooling::TfIdf, "кот дуб дуб"), "TF-IDF");
+
+    // SIF weights 0.25 / (0.25 + 0.75) = 0.25 and 0.25 / (0.25 + 0.25) = 0.5
+    let mut encoder = SentenceEncoder::new(&model, Pooling::Sif { a: 0.25 });
+    assert_close(&[0.125, 0.25], &encoder.encode("кот дуб").unwrap(), "SIF");
+
+    // both sentences give (0.125, 0.25), so the component is (1, 2) / √5
+    encoder.fit(&["кот дуб", "дуб кот кот дуб"]);
+    let component = encoder.component().unwrap().to_owned();
+    assert_close(&[1.0 / 5f32.sqrt(), 2.0 / 5f32.sqrt()], &component, "SIF component");
+    assert_close(&[0.0, 0.0], &encoder.encode("кот дуб").unwrap(), "SIF without component");
+    // (0.25, 0) minus its projection 0.25 / √5 on the component
+    assert_close(&[0.2, -0.1], &encoder.encode("кот").unwrap(), "SIF of a single word without component");
+}
+
+#[test]
+fn test_fasttext_embedding() {
+    if missing() {
+        return;
+    }
+
+    let model = FastText::with_model(&stem(UNSUPERVISED)).unwrap();
+
+    let word = model.word_to_vector("златом").unwrap();
+    let embedding = Embedding::from(model.0.word_to_vector("златом").unwrap());
+    assert_eq!(embedding.as_slice(), word.as_slice(), "check conversion from Vector");
+
+    let other = Embedding::from(model.word_to_vector("чахнет").unwrap());
+    assert!((embedding.cosine(&embedding) - 1.0).abs() < 1e-5, "check self similarity");
+    assert!(embedding.cosine(&other) < 1.0, "check similarity of different words");
+    assert_close(&(&embedding - &other + &other), &embedding, "arithmetic");
+}
+
+#[test]
+fn test_fasttext_vector_into() {
+    if missing() {
+        return;
+    }
+
+    for path in &[stem(UNSUPERVISED), stem(SUPERVISED)] {
+        let model = FastText::with_model(path).unwrap();
+        let mut out = vec![0f32; model.dimension()];
+
+        for word in &["златом", "лукоморье"] {
+            model.word_vector_into(word, &mut out).unwrap();
+            assert_eq!(Some(out.clone()), model.word_to_vector(word), "check word vector of {:?}", word);
+        }
+
+        let text = "Кащей над златом чахнет";
+        model.sentence_vector_into(text, &mut out).unwrap();
+        assert_eq!(Some(out.clone()), model.sentence_vector(text), "check sentence vector");
+
+        let mut wrong = vec![0f32; model.dimension() + 1];
+        match model.word_vector_into("златом", &mut wrong) {
+            Err(Err::ResErrorWrongDimension) => assert!(true),
+            res => assert!(false, "wrong dimension was accepted: {:?}", res),
+        }
+        match model.sentence_vector_into(text, &mut wrong[1..2]) {
+            Err(Err::ResErrorWrongDimension) => assert!(true),
+            res => assert!(false, "wrong dimension was accepted: {:?}", res),
+        }
+    }
+}
+
+#[test]
+fn test_fasttext_vector_into_not_loaded() {
+    let model = FastText::default();
+    assert_eq!(model.dimension(), 0, "check dimension of a model not loaded");
+
+    let mut out = vec![0f32; 0];
+    match model.word_vector_into("златом", &mut out) {
+        Err(Err::ResErrorModelNotInit) => assert!(true),
+        res => assert!(false, "got a word vector of a model not loaded: {:?}", res),
+    }
+    match model.sentence_vector_into("златом", &mut out) {
+        Err(Err::ResErrorModelNotInit) => assert!(true),
+        res => assert!(false, "got a sentence vector of a model not loaded: {:?}", res),
+    }
+}
+
+#[test]
+fn test_fasttext_embed() {
+    if missing() {
+        return;
+    }
+
+    let model = FastText::with_model(&stem(UNSUPERVISED)).unwrap();
+    let words = ["Кащей", "над", "златом", "чахнет", "лукоморье"];
+    let texts = ["Кащей над златом чахнет", "там на неведомых дорожках", ""];
+
+    for threads in &[1, 2, 8] {
+        let matrix = model.embed_words_parallel(&words, *threads).unwrap();
+        assert_eq!((matrix.rows(), matrix.cols()), (words.len(), model.dimension()), "check shape");
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(Some(matrix.row(i).to_owned()), model.word_to_vector(word), "check vector of {:?}", word);
+        }
+
+        let matrix = model.embed_sentences_parallel(&texts, *threads).unwrap();
+        assert_eq!((matrix.rows(), matrix.cols()), (texts.len(), model.dimension()), "check shape");
+        for (i, text) in texts.iter().enumerate() {
+            assert_eq!(Some(matrix.row(i).to_owned()), model.sentence_vector(text), "check vector of {:?}", text);
+        }
+    }
+
+    assert_eq!(model.embed_words(&[]).unwrap().rows(), 0, "check empty input");
+
+    match FastText::default().embed_words(&words) {
+        Err(Err::ResErrorModelNotInit) => assert!(true),
+        res => assert!(false, "embedded words with a model not loaded: {:?}", res),
+    }
+    match FastText::default().embed_sentences(&texts) {
+        Err(Err::ResErrorModelNotInit) => assert!(true),
+        res => assert!(false, "embedded texts with a model not loaded: {:?}", res),
+    }
+}
+
+#[test]
+fn test_fasttext_vocabulary_index() {
+    if missing() {
+        return;
+    }
+
+    let model = FastText::with_model(&stem(UNSUPERVISED)).unwrap();
+    let mut index = VocabularyIndex::build(&model, HnswParams::default()).unwrap();
+    index.set_ef(index.len());
+    assert_eq!(index.len(), model.0.get_dictionary().words_count() as usize, "check index size");
+
+    let word = "златом";
+    let vector = Embedding::from(model.word_to_vector(word).unwrap());
+    let nearest = index.nearest_to_word(word, 3);
+    assert_eq!(nearest.len(), 3, "check number of neighbors");
+    assert!(nearest.iter().all(|&(w, _)| w != word), "check the word itself is excluded");
+    for &(w, similarity) in &nearest {
+        let expected = vector.cosine(&Embedding::from(model.word_to_vector(w).unwrap()));
+        assert!((expected - similarity).abs() < 1e-4, "check similarity of {:?}", w);
+    }
+
+    let by_vector = index.nearest_to_vector(&vector, 1);
+    assert_eq!(by_vector[0].0, word, "check nearest to the word vector");
+    assert!(!index.nearest_to_sentence("Кащей над златом чахнет", 2).is_empty(), "check nearest to a sentence");
+
+    let path = scratch_path("unsupervised_model.bin");
+    let sidecar = ann::sidecar_path(&path);
+    let _ = fs::remove_file(&sidecar);
+    let built = VocabularyIndex::open_or_build(&model, &path, HnswParams::default()).unwrap();
+    assert!(sidecar.exists(), "check sidecar file is saved");
+
+    let mut opened = VocabularyIndex::open(&model, &sidecar).unwrap();
+    opened.set_ef(index.len());
+    let mut built = built;
+    built.set_ef(index.len());
+    assert_eq!(opened.nearest_to_word(word, 5), built.nearest_to_word(word, 5), "check saved index");
+
+    let other = FastText::with_model(&stem(SUPERVISED)).unwrap();
+    match VocabularyIndex::open(&other, &sidecar) {
+        Err(ann::Error::WrongModel { .. }) => assert!(true),
+        _ => assert!(false, "index of another model was opened"),
+    }
+
+    let mut bytes = fs::read(&sidecar).unwrap();
+    bytes[12] ^= 1;
+    fs::write(&sidecar, &bytes).unwrap();
+    match VocabularyIndex::open(&model, &sidecar) {
+        Err(ann::Error::WrongFingerprint) => assert!(true),
+        _ => assert!(false, "index with another fingerprint was opened"),
+    }
+    fs::remove_file(&sidecar).unwrap();
+}
+
+#[test]
+fn test_fasttext_info() {
+    if missing() {
+        return;
+    }
+
+    for &(ref path, kind) in &[(model_path(UNSUPERVISED), ModelKind::Skipgram), (model_path(SUPERVISED), ModelKind::Supervised)] {
+        let model = FastText::from_reader(File::open(path).unwrap()).unwrap();
+        let expected = format::Model::open(path).unwrap();
+
+        match model.info() {
+            Ok(info) => {
+                assert_eq!(info.version, expected.version, "check version of {:?}", path);
+                assert_eq!(info.args, expected.args, "check args of {:?}", path);
+                assert_eq!(info.model(), kind, "check model kind of {:?}", path);
+                assert_eq!(info.dim() as usize, model.dimension(), "check dimension of {:?}", path);
+                assert_eq!(info.nwords, expected.dictionary.nwords, "check words count of {:?}", path);
+                assert_eq!(info.nlabels, expected.dictionary.nlabels, "check labels count of {:?}", path);
+                assert_eq!(info.ntokens, expected.dictionary.ntokens, "check tokens count of {:?}", path);
+                assert!(!info.quantized, "check {:?} isn't quantized", path);
+            }
+            Err(err) => assert!(false, "failed to get info of {:?}: {:?}", path, err),
+        }
+    }
+
+    match FastText::default().info() {
+        Err(Err::ResErrorModelNotInit) => assert!(true),
+        res => assert!(false, "got info of a model not loaded: {:?}", res),
+    }
+}
+
 #[test]
 fn test_fasttext_predict_unsupervised() {
+    if missing() {
+        return;
+    }
+
     let mut model = FastTextWrapper::default();
 
-    match model.load_model(path(UNSUPERVISED_MODEL_PATH)) {
+    match model.load_model(&model_path(UNSUPERVISED)) {
         Ok(_) => assert!(true),
-        Err(err) => assert!(false, "Failed to load model {:?} with error {:?}", UNSUPERVISED_MODEL_PATH, err),
+        Err(err) => assert!(false, "Failed to load model {:?} with error {:?}", model_path(UNSUPERVISED), err),
     }
 
-    match model.load_vectors(path(UNSUPERVISED_VECTORS_PATH)) {
+    match model.load_vectors(&vectors_path(UNSUPERVISED)) {
         Ok(_) => assert!(true),
-        Err(err) => assert!(false, "Failed to load vectors {:?} with error {:?}", UNSUPERVISED_VECTORS_PATH, err),
+        Err(err) => assert!(false, "Failed to load vectors {:?} with error {:?}", vectors_path(UNSUPERVISED), err),
     }
 
     let text = String::from("Куда сходить вечером");
@@ -233,16 +685,20 @@ fn test_fasttext_predict_unsupervised() {
 
 #[test]
 fn test_fasttext_predict_supervised() {
+    if missing() {
+        return;
+    }
+
     let mut model = FastTextWrapper::default();
 
-    match model.load_model(path(SUPERVISED_MODEL_PATH)) {
+    match model.load_model(&model_path(SUPERVISED)) {
         Ok(_) => assert!(true),
-        Err(err) => assert!(false, "Failed to load model {:?} with error {:?}", SUPERVISED_MODEL_PATH, err),
+        Err(err) => assert!(false, "Failed to load model {:?} with error {:?}", model_path(SUPERVISED), err),
     }
 
-    match model.load_vectors(path(SUPERVISED_VECTORS_PATH)) {
+    match model.load_vectors(&vectors_path(SUPERVISED)) {
         Ok(_) => assert!(true),
-        Err(err) => assert!(false, "Failed to load vectors {:?} with error {:?}", SUPERVISED_VECTORS_PATH, err),
+        Err(err) => assert!(false, "Failed to load vectors {:?} with error {:?}", vectors_path(SUPERVISED), err),
     }
 
     let text = String::from("Куда сходить вечером");
@@ -266,4 +722,333 @@ fn test_fasttext_predict_supervised() {
         },
         Err(err) => assert!(false, "failed to predict for sentence {:?}, {:?}", text, err),
     };
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_fasttext_predict_probabilities() {
+    if missing() {
+        return;
+    }
+
+    let model = FastText::with_model(&stem(SUPERVISED)).unwrap();
+
+    match model.predict("Куда сходить вечером", 10, 0.0) {
+        Ok(predictions) => {
+            assert_eq!(predictions.len(), 3, "check number of labels");
+            assert!(predictions.iter().all(|p| p.probability > 0.0 && p.probability <= 1.0), "check probabilities");
+            assert!(predictions.windows(2).all(|w| w[0].probability >= w[1].probability), "check order");
+
+            let total: f32 = predictions.iter().map(|p| p.probability).sum();
+            assert!((total - 1.0).abs() < 0.01, "check probabilities sum up to 1: {}", total);
+        }
+        Err(err) => assert!(false, "failed to predict: {:?}", err),
+    }
+
+    match model.predict("Куда сходить вечером", 10, 0.5) {
+        Ok(predictions) => assert!(predictions.is_empty(), "check threshold: {:?}", predictions),
+        Err(err) => assert!(false, "failed to predict: {:?}", err),
+    }
+}
+
+#[test]
+fn test_fasttext_predict_lines() {
+    if missing() {
+        return;
+    }
+
+    let model = FastText::with_model(&stem(SUPERVISED)).unwrap();
+    let text = fs::read_to_string(SUPERVISED_TEXT_PATH).unwrap();
+
+    let mut count = 0;
+    for (line, predictions) in text.lines().zip(model.predict_lines(BufReader::new(File::open(SUPERVISED_TEXT_PATH).unwrap()), 2, 0.0)) {
+        match predictions {
+            Ok(predictions) => assert_eq!(predictions, model.predict(line, 2, 0.0).unwrap(), "check predictions of {}", line),
+            Err(err) => assert!(false, "failed to predict {}: {:?}", line, err),
+        }
+        count += 1;
+    }
+    assert_eq!(count, text.lines().count(), "check one item per line");
+
+    let lines: Vec<_> = model.predict_lines("Куда сходить вечером\r\n\n".as_bytes(), 1, 0.0).collect();
+    assert_eq!(lines.len(), 2, "check number of lines");
+    assert_eq!(lines[0].as_ref().unwrap(), &model.predict("Куда сходить вечером", 1, 0.0).unwrap(), "check line endings");
+
+    let lines: Vec<_> = model.predict_lines("Доброе\0утро\nКуда сходить вечером\n".as_bytes(), 1, 0.0).collect();
+    match lines[0] {
+        Err(ref err) => assert_eq!(err.kind(), io::ErrorKind::InvalidData, "check a line with NUL is invalid"),
+        Ok(ref predictions) => assert!(false, "predicted a line with NUL: {:?}", predictions),
+    }
+    assert!(lines[1].is_ok(), "check the next line is predicted");
+}
+
+#[test]
+fn test_fasttext_test() {
+    if missing() {
+        return;
+    }
+
+    let model = FastText::with_model(&stem(SUPERVISED)).unwrap();
+    let lines = fs::read_to_string(SUPERVISED_TEXT_PATH).unwrap().lines().filter(|line| !line.trim().is_empty()).count();
+
+    match model.test(BufReader::new(File::open(SUPERVISED_TEXT_PATH).unwrap()), 1, 0.0) {
+        Ok(evaluation) => {
+            assert_eq!(evaluation.examples as usize, lines, "check examples");
+            assert_eq!(evaluation.predicted, evaluation.examples, "check one prediction per example");
+            assert!(evaluation.precision() >= 0.0 && evaluation.precision() <= 1.0, "check precision");
+            assert!(evaluation.label("__label__приветствие").unwrap().gold > 0, "check per label metrics");
+        }
+        Err(err) => assert!(false, "failed to test the model: {:?}", err),
+    }
+
+    match model.test("__label__лукоморье Доброе утро\n__label__лукоморье __label__приветствие Доброе утро\n".as_bytes(), 1, 0.0) {
+        Ok(evaluation) => {
+            assert_eq!(evaluation.examples, 1, "check a line of unknown labels is skipped");
+            assert_eq!(evaluation.label("__label__лукоморье"), None, "check unknown labels are ignored");
+        }
+        Err(err) => assert!(false, "failed to test the model: {:?}", err),
+    }
+
+    let unsupervised = FastText::with_model(&stem(UNSUPERVISED)).unwrap();
+    assert!(unsupervised.test("__label__a дуб\n".as_bytes(), 1, 0.0).is_err(), "check an unsupervised model fails");
+}
+
+#[test]
+fn test_fasttext_ngram_vectors() {
+    if missing() {
+        return;
+    }
+
+    let model = FastText::with_model(&stem(UNSUPERVISED)).unwrap();
+
+    let ngrams = model.ngram_vectors("дуб");
+    assert_eq!(ngrams[0].0, "дуб", "check the word comes first");
+    assert!(ngrams.iter().any(|&(ref ngram, _)| ngram == "<ду"), "check character n-grams: {:?}", ngrams.iter().map(|n| &n.0).collect::<Vec<_>>());
+    assert!(ngrams.iter().all(|&(_, ref vec)| vec.len() == model.dimension()), "check dimension");
+
+    let oov = model.ngram_vectors("лукоморье");
+    assert!(oov.iter().all(|&(ref ngram, _)| ngram != "лукоморье"), "check an unknown word has only n-grams");
+}
+
+#[test]
+fn test_fasttext_exact_index() {
+    if missing() {
+        return;
+    }
+
+    let model = FastText::with_model(&stem(UNSUPERVISED)).unwrap();
+    let index = ExactIndex::new(&model).unwrap();
+    let mut hnsw = VocabularyIndex::build(&model, HnswParams::default()).unwrap();
+    hnsw.set_ef(hnsw.len());
+
+    let word = "златом";
+    let nearest = index.nearest_to_word(word, 5);
+    assert_eq!(nearest.len(), 5, "check number of neighbors");
+    assert!(nearest.iter().all(|&(w, _)| w != word), "check the word itself is excluded");
+    assert!(nearest.windows(2).all(|w| w[0].1 >= w[1].1), "check order");
+    assert_eq!(nearest[0].0, hnsw.nearest_to_word(word, 1)[0].0, "check exact and approximate search agree");
+
+    let analogies = index.analogies("дуб", "зелёный", "кот", 3);
+    assert_eq!(analogies.len(), 3, "check number of analogies");
+    assert!(analogies.iter().all(|&(w, _)| w != "дуб" && w != "зелёный" && w != "кот"), "check query words are excluded");
+}
+
+#[test]
+fn test_fasttext_analogies() {
+    // "мужчина" and "женщина" have the same direction, so the normalized query is the one of "король"
+    let words = [("король", 1), ("мужчина", 1), ("женщина", 1), ("царь", 1), ("царица", 1)];
+    let input: [&[f32]; 5] = [&[3.0, 0.0], &[0.0, 4.0], &[0.0, 0.5], &[1.0, 0.0], &[1.0, -1.2]];
+    let model = FastText::from_bytes(&model_bytes(&words, 0, &input)).unwrap();
+    let index = ExactIndex::new(&model).unwrap();
+
+    let analogies = index.analogies("король", "мужчина", "женщина", 2);
+    assert_eq!(analogies.iter().map(|&(word, _)| word).collect::<Vec<_>>(), vec!["царь", "царица"], "check analogies");
+    assert_close(&[1.0, 1.0 / 2.44f32.sqrt()], &analogies.iter().map(|&(_, similarity)| similarity).collect::<Vec<_>>(), "similarities");
+}
+
+#[cfg(not(feature = "pure-rust"))]
+#[test]
+fn test_fasttext_train() {
+    let mut args = TrainArgs::skipgram(UNSUPERVISED_TEXT_PATH);
+    args.dim = 10;
+    args.min_count = 1;
+    args.epoch = 1;
+    args.thread = 1;
+    args.verbose = 0;
+
+    let model = match FastText::train(&args) {
+        Ok(model) => model,
+        Err(err) => panic!("failed to train a model: {:?}", err),
+    };
+    assert_eq!(model.dimension(), 10, "check dimension");
+
+    let path = scratch_path("trained_model.bin");
+    model.save_model(&path).unwrap();
+    let loaded = FastText::from_reader(File::open(&path).unwrap()).unwrap();
+    assert_eq!(loaded.word_to_vector("дуб"), model.word_to_vector("дуб"), "check saved model");
+    fs::remove_file(&path).unwrap();
+
+    match FastText::train(&TrainArgs::skipgram(UNKNOWN_PATH)) {
+        Err(Err::ResErrorNotOpen) => assert!(true),
+        res => assert!(false, "trained on an unknown path: {:?}", res.map(|_| ())),
+    }
+
+    let mut args = TrainArgs::supervised(SUPERVISED_TEXT_PATH);
+    args.dim = 10;
+    args.thread = 1;
+    args.verbose = 0;
+    let mut model = FastText::train(&args).unwrap();
+    let labels = model.predict("Доброе утро", 1, 0.0).unwrap();
+
+    let qargs = QuantizeArgs { dsub: 2, thread: 1, verbose: 0, ..QuantizeArgs::default() };
+    match model.quantize(&qargs) {
+        Ok(_) => assert!(model.info().unwrap().quantized, "check the model is quantized"),
+        Err(err) => assert!(false, "failed to quantize the model: {:?}", err),
+    }
+    assert_eq!(model.predict("Доброе утро", 1, 0.0).unwrap().len(), labels.len(), "check the quantized model predicts");
+}
+
+#[cfg(not(feature = "pure-rust"))]
+#[test]
+fn test_fasttext_quantize_mapped() {
+    let mut model = FastText::with_mapped_model(&model_path(SUPERVISED)).unwrap();
+
+    let retrain = QuantizeArgs {
+        input: Some(SUPERVISED_TEXT_PATH.into()),
+        cutoff: 100,
+        retrain: true,
+        thread: 1,
+        verbose: 0,
+        ..QuantizeArgs::default()
+    };
+    match model.quantize(&retrain) {
+        Err(Err::RerErrorWrongModel) => assert!(true),
+        res => assert!(false, "retrained a mapped model: {:?}", res),
+    }
+
+    let qargs = QuantizeArgs { thread: 1, verbose: 0, ..QuantizeArgs::default() };
+    match model.quantize(&qargs) {
+        Ok(_) => assert!(model.info().unwrap().quantized, "check the mapped model is quantized"),
+        Err(err) => assert!(false, "failed to quantize the mapped model: {:?}", err),
+    }
+    assert!(!model.predict("Доброе утро", 1, 0.0).unwrap().is_empty(), "check the quantized model predicts");
+}
+
+#[cfg(not(feature = "pure-rust"))]
+#[test]
+fn test_fasttext_train_from_iterators() {
+    let mut args = TrainArgs::supervised(UNKNOWN_PATH);
+    args.dim = 10;
+    args.thread = 1;
+    args.verbose = 0;
+
+    let examples = vec![
+        (vec![String::from("greeting")], String::from("Доброе утро")),
+        (vec![String::from("__label__question")], String::from("Куда сходить\nвечером")),
+    ];
+    match FastText::train_supervised(examples, &args) {
+        Ok(model) => {
+            let labels: Vec<String> = model.predict("Доброе утро", 2, 0.0).unwrap().into_iter().map(|p| p.label).collect();
+            assert_eq!(labels.len(), 2, "check number of labels");
+            assert!(labels.contains(&String::from("__label__greeting")), "check label prefix: {:?}", labels);
+        }
+        Err(err) => assert!(false, "failed to train a supervised model: {:?}", err),
+    }
+
+    let mut args = TrainArgs::skipgram(UNKNOWN_PATH);
+    args.dim = 10;
+    args.min_count = 1;
+    args.epoch = 1;
+    args.thread = 1;
+    args.verbose = 0;
+
+    let text = fs::read_to_string(UNSUPERVISED_TEXT_PATH).unwrap();
+    match FastText::train_unsupervised(text.lines(), &args) {
+        Ok(model) => assert_eq!(model.word_to_vector("дуб").map(|v| v.len()), Some(10), "check a word vector"),
+        Err(err) => assert!(false, "failed to train an unsupervised model: {:?}", err),
+    }
+}
+
+#[cfg(not(feature = "pure-rust"))]
+#[test]
+fn test_fasttext_train_deterministic() {
+    let train = |seed: i32, name: &str| {
+        let mut args = TrainArgs::supervised(SUPERVISED_TEXT_PATH).deterministic(seed);
+        args.dim = 10;
+        args.verbose = 0;
+
+        let path = scratch_path(name);
+        FastText::train(&args).unwrap().save_model(&path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        bytes
+    };
+
+    let first = train(7, "deterministic_1.bin");
+    assert!(first == train(7, "deterministic_2.bin"), "check identical models with the same seed");
+    assert!(first != train(8, "deterministic_3.bin"), "check other models with another seed");
+}
+
+#[cfg(not(feature = "pure-rust"))]
+#[test]
+fn test_fasttext_train_pretrained_vectors() {
+    let vectors = FastText::with_model(&stem(UNSUPERVISED)).unwrap();
+    let dim = vectors.dimension() as i32;
+
+    let mut args = TrainArgs::supervised(SUPERVISED_TEXT_PATH);
+    args.dim = dim;
+    args.thread = 1;
+    args.verbose = 0;
+
+    args.pretrained_vectors = Some(PretrainedVectors::File(vectors_path(UNSUPERVISED)));
+    match FastText::train(&args) {
+        Ok(model) => assert_eq!(model.dimension(), dim as usize, "check dimension"),
+        Err(err) => assert!(false, "failed to train with a vectors file: {:?}", err),
+    }
+
+    args.pretrained_vectors = Some(PretrainedVectors::Model(Arc::new(vectors)));
+    match FastText::train(&args) {
+        Ok(model) => assert_eq!(model.dimension(), dim as usize, "check dimension"),
+        Err(err) => assert!(false, "failed to train with a loaded model: {:?}", err),
+    }
+
+    args.dim = dim + 1;
+    match FastText::train(&args) {
+        Err(Err::ResErrorWrongDimension) => assert!(true),
+        res => assert!(false, "trained with vectors of another dimension: {:?}", res.map(|_| ())),
+    }
+
+    args.pretrained_vectors = Some(PretrainedVectors::File(vectors_path(UNSUPERVISED)));
+    match FastText::train(&args) {
+        Err(Err::ResErrorWrongDimension) => assert!(true),
+        res => assert!(false, "trained with a vectors file of another dimension: {:?}", res.map(|_| ())),
+    }
+}
+
+#[cfg(not(feature = "pure-rust"))]
+#[test]
+fn test_fasttext_fine_tune() {
+    let mut args = TrainArgs::supervised(SUPERVISED_TEXT_PATH);
+    args.dim = 10;
+    args.bucket = 1000;
+    args.thread = 1;
+    args.verbose = 0;
+    let base = FastText::train(&args).unwrap();
+    let before = base.predict("Доброе утро", 3, 0.0).unwrap();
+
+    let examples = vec![(vec![String::from("evening")], String::from("Куда сходить вечером"))];
+    let tuned = match base.fine_tune(examples, &TrainArgs { dim: 100, ..args.clone() }) {
+        Ok(model) => model,
+        Err(err) => panic!("failed to fine-tune a model: {:?}", err),
+    };
+
+    assert_eq!(tuned.dimension(), 10, "check the dimension of the base model is kept");
+    assert_eq!(tuned.predict("Куда сходить вечером", 1, 0.0).unwrap()[0].label, "__label__evening", "check new label");
+    assert_eq!(base.predict("Доброе утро", 3, 0.0).unwrap(), before, "check the base model is unchanged");
+
+    let unsupervised = FastText::with_model(&stem(UNSUPERVISED)).unwrap();
+    match unsupervised.fine_tune(Vec::new(), &args) {
+        Err(Err::RerErrorWrongModel) => assert!(true),
+        res => assert!(false, "fine-tuned an unsupervised model: {:?}", res.map(|_| ())),
+    }
+}
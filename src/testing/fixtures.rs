@@ -0,0 +1,162 @@
+//! The test models, trained on first use from `unsupervised_text.txt` and `supervised_text.txt`
+//! into `target/fasttext-rs-fixtures`, so tests need neither the fastText CLI nor `test-data`.
+//!
+//! The pure-Rust backend can't train, its tests read the models of `test-data` built as the readme says
+//! and are skipped when they are missing.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+#[cfg(not(feature = "pure-rust"))]
+use std::sync::Once;
+
+use format::{FILEFORMAT_MAGIC, VERSION};
+
+pub const UNSUPERVISED: &'static str = "unsupervised_model";
+pub const SUPERVISED: &'static str = "supervised_model";
+
+#[cfg(not(feature = "pure-rust"))]
+static TRAINED: Once = Once::new();
+
+/// A stable directory, shared by test runs and removed by `cargo clean`.
+fn dir() -> PathBuf {
+    let target = option_env!("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target"));
+    let dir = target.join("fasttext-rs-fixtures");
+    fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+#[cfg(not(feature = "pure-rust"))]
+fn train() {
+    use std::fs::File;
+    use std::io::BufWriter;
+
+    use train::TrainArgs;
+    use FastText;
+
+    // the arguments of the readme commands, with one thread for reproducible models
+    for &(name, ref args) in &[
+        (UNSUPERVISED, TrainArgs::skipgram("./unsupervised_text.txt")),
+        (SUPERVISED, TrainArgs::supervised("./supervised_text.txt")),
+    ] {
+        let args = TrainArgs { verbose: 0, ..args.clone().deterministic(0) };
+        let model = match FastText::train(&args) {
+            Ok(model) => model,
+            Err(err) => panic!("failed to train the {} fixture: {:?}", name, err),
+        };
+
+        // written under names of this process then renamed, as concurrent test runs share the directory
+        let bin = scratch_path(&format!("{}.bin", name));
+        let vec = scratch_path(&format!("{}.vec", name));
+        model.save_model(&bin).unwrap();
+        model.save_word2vec_text(BufWriter::new(File::create(&vec).unwrap())).unwrap();
+
+        let stem = dir().join(name);
+        fs::rename(&bin, stem.with_extension("bin")).unwrap();
+        fs::rename(&vec, stem.with_extension("vec")).unwrap();
+    }
+}
+
+/// The path of a fixture without extension, as `FastText::with_model` takes it.
+#[cfg(not(feature = "pure-rust"))]
+pub fn stem(name: &str) -> PathBuf {
+    TRAINED.call_once(train);
+
+    dir().join(name)
+}
+
+#[cfg(feature = "pure-rust")]
+pub fn stem(name: &str) -> PathBuf {
+    PathBuf::from("./test-data").join(name)
+}
+
+/// Whether the trained models are missing, so that tests needing them return early.
+#[cfg(not(feature = "pure-rust"))]
+pub fn missing() -> bool {
+    false
+}
+
+/// Whether the trained models are missing, so that tests needing them return early:
+/// the pure-Rust backend can't train them and reads them from `test-data`.
+#[cfg(feature = "pure-rust")]
+pub fn missing() -> bool {
+    let missing = [UNSUPERVISED, SUPERVISED].iter().any(|name| !model_path(name).exists() || !vectors_path(name).exists());
+    if missing {
+        eprintln!("skipped: the models of ./test-data are missing, see the readme");
+    }
+
+    missing
+}
+
+/// The `.bin` file of a fixture.
+pub fn model_path(name: &str) -> PathBuf {
+    stem(name).with_extension("bin")
+}
+
+/// The `.vec` file of a fixture.
+pub fn vectors_path(name: &str) -> PathBuf {
+    stem(name).with_extension("vec")
+}
+
+/// A path for a file written by a test, unique to the test process; tests remove their files.
+pub fn scratch_path(name: &str) -> PathBuf {
+    dir().join(format!("{}-{}", process::id(), name))
+}
+
+fn write_i32(buf: &mut Vec<u8>, value: i32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_i64(buf: &mut Vec<u8>, value: i64) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+/// A skipgram model written byte by byte: `words` with their counts, n-grams of `ngram` characters
+/// (none for 0) hashed into the input rows following those of the words, and a null output matrix.
+/// The dimension is the length of the input rows.
+pub fn model_bytes(words: &[(&str, i64)], ngram: i32, input: &[&[f32]]) -> Vec<u8> {
+    let dim = input[0].len();
+    let bucket = (input.len() - words.len()) as i32;
+
+    let mut buf = Vec::new();
+    write_i32(&mut buf, FILEFORMAT_MAGIC);
+    write_i32(&mut buf, VERSION);
+    // dim ws epoch minCount neg wordNgrams loss model bucket minn maxn lrUpdateRate
+    for &value in &[dim as i32, 5, 5, 1, 5, 1, 2, 2, bucket, ngram, ngram, 100] {
+        write_i32(&mut buf, value);
+    }
+    buf.extend_from_slice(&1e-4f64.to_le_bytes());
+
+    write_i32(&mut buf, words.len() as i32);
+    write_i32(&mut buf, words.len() as i32);
+    write_i32(&mut buf, 0);
+    write_i64(&mut buf, words.iter().map(|&(_, count)| count).sum());
+    write_i64(&mut buf, -1);
+    for &(word, count) in words {
+        buf.extend_from_slice(word.as_bytes());
+        buf.push(0);
+        write_i64(&mut buf, count);
+        buf.push(0);
+    }
+
+    let output = vec![0f32; words.len() * dim];
+    for (rows, values) in vec![(input.len(), input.concat()), (words.len(), output)] {
+        buf.push(0);
+        write_i64(&mut buf, rows as i64);
+        write_i64(&mut buf, dim as i64);
+        for value in values {
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    buf
+}
+
+/// A model of dimension 4 with the word "златом", 3-grams and one bucket, so that every n-gram
+/// has the second input row and word vectors are known in advance.
+pub fn golden_model() -> Vec<u8> {
+    model_bytes(&[("златом", 1)], 3, &[&[1.0, 2.0, 3.0, 4.0], &[0.5, -0.5, 0.25, 0.0]])
+}
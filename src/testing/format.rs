@@ -0,0 +1,115 @@
+use format::*;
+use testing::fixtures::{missing, model_path, vectors_path, SUPERVISED, UNSUPERVISED};
+use std::path::Path;
+
+#[test]
+fn test_format_read_unsupervised() {
+    if missing() {
+        return;
+    }
+
+    let model = match Model::open(&model_path(UNSUPERVISED)) {
+        Ok(model) => model,
+        Err(err) => panic!("failed to read model {:?} with error {:?}", model_path(UNSUPERVISED), err),
+    };
+
+    assert_eq!(model.version, VERSION, "check version");
+    assert_eq!(model.args.model, ModelKind::Skipgram, "check model kind");
+    assert_eq!(model.args.dim, 100, "check dimension");
+    assert_eq!(model.is_quantized(), false, "check quantization");
+    assert_eq!(model.dictionary.nlabels, 0, "check labels");
+    assert_eq!(model.dictionary.words()[22].word, "златом", "check dictionary");
+    assert_eq!(
+        model.input.rows(),
+        model.dictionary.nwords as usize + model.args.bucket as usize,
+        "check input matrix"
+    );
+    assert_eq!(model.output.rows(), model.dictionary.nwords as usize, "check output matrix");
+}
+
+#[test]
+fn test_format_read_supervised() {
+    if missing() {
+        return;
+    }
+
+    let model = match Model::open(&model_path(SUPERVISED)) {
+        Ok(model) => model,
+        Err(err) => panic!("failed to read model {:?} with error {:?}", model_path(SUPERVISED), err),
+    };
+
+    assert_eq!(model.args.model, ModelKind::Supervised, "check model kind");
+    assert_eq!(model.dictionary.nlabels, 3, "check labels");
+    assert!(
+        model.dictionary.labels().iter().all(|entry| entry.entry_type == EntryType::Label && entry.word.starts_with("__label__")),
+        "check label entries"
+    );
+    assert_eq!(model.output.rows(), 3, "check output matrix");
+}
+
+#[test]
+fn test_format_read_wrong_file() {
+    if missing() {
+        return;
+    }
+
+    match Model::open(&vectors_path(UNSUPERVISED)) {
+        Err(Error::WrongMagic(_)) => assert!(true),
+        res => assert!(false, "failed to raise a wrong magic error, got {:?}", res.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_format_read_vec() {
+    if missing() {
+        return;
+    }
+
+    let model = Model::open(&model_path(UNSUPERVISED)).unwrap();
+
+    let reader = match vec::Reader::open(&vectors_path(UNSUPERVISED)) {
+        Ok(reader) => reader,
+        Err(err) => panic!("failed to read vectors {:?} with error {:?}", vectors_path(UNSUPERVISED), err),
+    };
+    assert_eq!(reader.rows(), model.dictionary.nwords as usize, "check rows");
+    assert_eq!(reader.dim(), model.args.dim as usize, "check dimension");
+
+    match reader.read_all() {
+        Ok((words, matrix)) => {
+            assert_eq!(words[22], "златом", "check words");
+            assert_eq!(matrix.rows, words.len(), "check matrix");
+        }
+        Err(err) => assert!(false, "failed to read vectors {:?} with error {}", vectors_path(UNSUPERVISED), err),
+    }
+}
+
+#[test]
+fn test_format_inspect_file() {
+    if missing() {
+        return;
+    }
+
+    for path in &[model_path(UNSUPERVISED), model_path(SUPERVISED)] {
+        let model = Model::open(Path::new(path)).unwrap();
+
+        match inspect::inspect_file(Path::new(path)) {
+            Ok(report) => {
+                assert!(report.is_valid(), "check {:?} is valid: {:?}", path, report.issue);
+                assert_eq!(report.args, Some(model.args.clone()), "check args of {:?}", path);
+                assert_eq!(report.sections[3].shape, Some((model.input.rows(), model.input.cols())), "check input of {:?}", path);
+                assert_eq!(report.sections[4].shape, Some((model.output.rows(), model.output.cols())), "check output of {:?}", path);
+            }
+            Err(err) => assert!(false, "failed to inspect {:?} with error {:?}", path, err),
+        }
+    }
+
+    match inspect::inspect_file(&vectors_path(UNSUPERVISED)) {
+        Ok(inspect::FileReport { issue: Some(inspect::Issue::WrongMagic(_)), .. }) => assert!(true),
+        res => assert!(false, "failed to report a wrong magic, got {:?}", res.map(|report| report.issue)),
+    }
+
+    match inspect::inspect_file(Path::new("unknown path")) {
+        Err(::Err::ResErrorNotOpen) => assert!(true),
+        res => assert!(false, "failed to raise a not open error, got {:?}", res.map(|report| report.issue)),
+    }
+}
@@ -1,4 +1,10 @@
 extern crate test;
 
 #[cfg(test)]
-mod fasttext;
\ No newline at end of file
+mod fasttext;
+#[cfg(test)]
+pub mod fixtures;
+#[cfg(test)]
+mod format;
+#[cfg(all(test, not(feature = "pure-rust")))]
+mod native;
@@ -0,0 +1,91 @@
+use fasttext::FastTextWrapper;
+use format;
+use native::Model;
+use predict::PredictRecord;
+use testing::fixtures::{model_path, vectors_path, SUPERVISED, UNSUPERVISED};
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+static UNSUPERVISED_TEXT_PATH: &'static str = "./unsupervised_text.txt";
+static SUPERVISED_TEXT_PATH: &'static str = "./supervised_text.txt";
+
+const EPS: f32 = 1e-6;
+
+fn load(model_file: PathBuf, vectors_file: Option<PathBuf>) -> (FastTextWrapper, Model) {
+    let mut wrapper = FastTextWrapper::default();
+    wrapper.load_model(&model_file).unwrap();
+
+    let mut model = Model::new(format::Model::open(&model_file).unwrap());
+
+    if let Some(vectors_file) = vectors_file {
+        wrapper.load_vectors(&vectors_file).unwrap();
+        model.load_vectors(&mut File::open(vectors_file).unwrap()).unwrap();
+    }
+
+    (wrapper, model)
+}
+
+fn assert_vectors_eq(expected: &[f32], got: &[f32], msg: &str) {
+    assert_eq!(expected.len(), got.len(), "check dimension of {}", msg);
+    assert!(
+        expected.iter().zip(got).all(|(e, g)| (e - g).abs() < EPS),
+        "check vector of {}: {:?} != {:?}", msg, expected, got
+    );
+}
+
+fn assert_equivalent(wrapper: &FastTextWrapper, model: &Model, text_path: &str) {
+    let dict = wrapper.get_dictionary();
+    assert_eq!(dict.words_count(), model.dictionary().nwords(), "check words count");
+    assert_eq!(dict.tokens_count(), model.dictionary().ntokens(), "check tokens count");
+    assert_eq!(dict.word_counts(), model.dictionary().counts(format::EntryType::Word), "check word counts");
+
+    for i in 0..dict.words_count() {
+        let word = dict.get_word(i as i64).unwrap();
+        assert_eq!(model.dictionary().word(i), Some(word.as_str()), "check dictionary order");
+
+        assert_vectors_eq(wrapper.word_to_vector(&word).unwrap().as_slice(), &model.word_vector(&word), &word);
+    }
+
+    for oov in &["лукоморье", "несуществующее"] {
+        assert_vectors_eq(wrapper.word_to_vector(oov).unwrap().as_slice(), &model.word_vector(oov), oov);
+
+        let (expected, got) = (wrapper.ngram_vectors(oov), model.ngram_vectors(oov));
+        assert_eq!(expected.len(), got.len(), "check ngrams count of {}", oov);
+        for ((expected_ngram, expected), (ngram, got)) in expected.iter().zip(&got) {
+            assert_eq!(expected_ngram, ngram, "check ngrams of {}", oov);
+            assert_vectors_eq(expected, got, ngram);
+        }
+    }
+
+    for line in fs::read_to_string(text_path).unwrap().lines() {
+        assert_vectors_eq(wrapper.sentence_to_vector(line).unwrap().as_slice(), &model.sentence_vector(line), line);
+
+        match (wrapper.predict(line, 10), model.predict(line, 10, 0.0)) {
+            (Ok(expected), Ok(got)) => assert_eq!(
+                expected.as_slice(),
+                got.into_iter().map(|(prediction, word)| PredictRecord::new(prediction, word)).collect::<Vec<_>>().as_slice(),
+                "check prediction of {:?}", line
+            ),
+            (Err(_), Err(_)) => assert!(true),
+            (expected, got) => assert!(false, "check prediction of {:?}: {:?} != {:?}", line, expected.map(|_| ()), got),
+        }
+    }
+}
+
+#[test]
+fn test_native_unsupervised() {
+    let (wrapper, model) = load(model_path(UNSUPERVISED), None);
+    assert_equivalent(&wrapper, &model, UNSUPERVISED_TEXT_PATH);
+
+    let (wrapper, model) = load(model_path(UNSUPERVISED), Some(vectors_path(UNSUPERVISED)));
+    assert_equivalent(&wrapper, &model, UNSUPERVISED_TEXT_PATH);
+}
+
+#[test]
+fn test_native_supervised() {
+    let (wrapper, model) = load(model_path(SUPERVISED), None);
+    assert_equivalent(&wrapper, &model, SUPERVISED_TEXT_PATH);
+
+    let (wrapper, model) = load(model_path(SUPERVISED), Some(vectors_path(SUPERVISED)));
+    assert_equivalent(&wrapper, &model, SUPERVISED_TEXT_PATH);
+}
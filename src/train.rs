@@ -0,0 +1,410 @@
+//! Training and quantization of models, as the `supervised`, `skipgram`, `cbow` and `quantize`
+//! commands of fastText do.
+
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use fasttext::FastTextWrapper;
+use format::vec;
+use format::{Loss, ModelKind};
+use wordvector_base::WordVectorModel;
+use {Err, FastText};
+
+/// The vectors initializing the input matrix of a model, `pretrainedVectors` in fastText.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PretrainedVectors {
+    /// A `.vec` file.
+    File(PathBuf),
+    /// A loaded model, asked for the vectors of the words of the training data on the calling thread.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Model(Arc<dyn WordVectorModel>),
+}
+
+impl fmt::Debug for PretrainedVectors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PretrainedVectors::File(ref path) => f.debug_tuple("File").field(path).finish(),
+            PretrainedVectors::Model(_) => f.write_str("Model(..)"),
+        }
+    }
+}
+
+/// Models are equal when they are the same one.
+impl PartialEq for PretrainedVectors {
+    fn eq(&self, other: &PretrainedVectors) -> bool {
+        match (self, other) {
+            (&PretrainedVectors::File(ref a), &PretrainedVectors::File(ref b)) => a == b,
+            (&PretrainedVectors::Model(ref a), &PretrainedVectors::Model(ref b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// The arguments of training, `Args` in fastText.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TrainArgs {
+    /// The training data: one sentence, or labels and a text for supervised models, per line.
+    pub input: PathBuf,
+    pub model: ModelKind,
+    pub loss: Loss,
+    pub lr: f64,
+    pub lr_update_rate: i32,
+    pub dim: i32,
+    pub ws: i32,
+    pub epoch: i32,
+    pub min_count: i32,
+    pub min_count_label: i32,
+    pub neg: i32,
+    pub word_ngrams: i32,
+    pub bucket: i32,
+    pub minn: i32,
+    pub maxn: i32,
+    pub thread: i32,
+    pub t: f64,
+    pub label: String,
+    pub verbose: i32,
+    /// The vectors initializing the input matrix, which must have `dim` dimensions.
+    pub pretrained_vectors: Option<PretrainedVectors>,
+    /// The seed of the random initialization of the input matrix.
+    pub seed: i32,
+}
+
+impl TrainArgs {
+    /// The defaults of `fasttext skipgram`.
+    pub fn skipgram<P: AsRef<Path>>(input: P) -> TrainArgs {
+        TrainArgs {
+            input: input.as_ref().to_owned(),
+            model: ModelKind::Skipgram,
+            loss: Loss::NegativeSampling,
+            lr: 0.05,
+            lr_update_rate: 100,
+            dim: 100,
+            ws: 5,
+            epoch: 5,
+            min_count: 5,
+            min_count_label: 0,
+            neg: 5,
+            word_ngrams: 1,
+            bucket: 2000000,
+            minn: 3,
+            maxn: 6,
+            thread: 12,
+            t: 1e-4,
+            label: String::from("__label__"),
+            verbose: 2,
+            pretrained_vectors: None,
+            seed: 0,
+        }
+    }
+
+    /// The defaults of `fasttext cbow`.
+    pub fn cbow<P: AsRef<Path>>(input: P) -> TrainArgs {
+        TrainArgs {
+            model: ModelKind::Cbow,
+            ..TrainArgs::skipgram(input)
+        }
+    }
+
+    /// The defaults of `fasttext supervised`.
+    pub fn supervised<P: AsRef<Path>>(input: P) -> TrainArgs {
+        TrainArgs {
+            model: ModelKind::Supervised,
+            loss: Loss::Softmax,
+            lr: 0.1,
+            min_count: 1,
+            minn: 0,
+            maxn: 0,
+            ..TrainArgs::skipgram(input)
+        }
+    }
+
+    /// The same arguments with one thread and `seed`, so that training on the same input
+    /// gives byte-identical models.
+    pub fn deterministic(self, seed: i32) -> TrainArgs {
+        TrainArgs {
+            thread: 1,
+            seed,
+            ..self
+        }
+    }
+}
+
+/// The arguments of `fasttext quantize`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuantizeArgs {
+    /// The training data used to fine-tune the embeddings kept by `cutoff` when `retrain` is set.
+    pub input: Option<PathBuf>,
+    /// The number of words and n-grams to keep, 0 to keep all.
+    pub cutoff: usize,
+    pub retrain: bool,
+    /// Quantizes the norms separately.
+    pub qnorm: bool,
+    /// Quantizes the classifier too.
+    pub qout: bool,
+    /// The size of each sub-vector.
+    pub dsub: i32,
+    pub epoch: i32,
+    pub lr: f64,
+    pub thread: i32,
+    pub verbose: i32,
+}
+
+impl Default for QuantizeArgs {
+    fn default() -> QuantizeArgs {
+        QuantizeArgs {
+            input: None,
+            cutoff: 0,
+            retrain: false,
+            qnorm: false,
+            qout: false,
+            dsub: 2,
+            epoch: 5,
+            lr: 0.1,
+            thread: 12,
+            verbose: 2,
+        }
+    }
+}
+
+static STAGED_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Training data or vectors written to a temporary file, removed when dropped.
+struct StagedFile(PathBuf);
+
+impl StagedFile {
+    fn path(extension: &str) -> PathBuf {
+        let name = format!("fasttext-rs-{}-{}.{}", process::id(), STAGED_FILES.fetch_add(1, Ordering::SeqCst), extension);
+        env::temp_dir().join(name)
+    }
+
+    /// Creates the file, failing rather than following a file or a link put at its path by someone else.
+    fn create(extension: &str) -> io::Result<(StagedFile, File)> {
+        let path = StagedFile::path(extension);
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let file = options.open(&path)?;
+
+        Ok((StagedFile(path), file))
+    }
+
+    fn write<I: IntoIterator<Item = String>>(lines: I) -> io::Result<StagedFile> {
+        let (staged, file) = StagedFile::create("txt")?;
+
+        let mut writer = BufWriter::new(file);
+        for line in lines {
+            writer.write_all(line.as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+
+        Ok(staged)
+    }
+}
+
+impl Drop for StagedFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Checks the header of a `.vec` file as `checkVectorsFile` would.
+fn check_vectors_file(path: &Path, dim: i32) -> Result<(), Err> {
+    let reader = vec::Reader::open(path).map_err(|_| Err::ResErrorNotOpen)?;
+
+    if reader.dim() != dim as usize {
+        return Err(Err::ResErrorWrongDimension);
+    }
+
+    Ok(())
+}
+
+/// Writes the vectors `model` has for the words of the training data to a `.vec` file.
+fn stage_vectors(model: &dyn WordVectorModel, args: &TrainArgs) -> Result<StagedFile, Err> {
+    let input = File::open(&args.input).map_err(|_| Err::ResErrorNotOpen)?;
+
+    let mut words = HashSet::new();
+    let mut vectors = Vec::new();
+    for line in BufReader::new(input).lines() {
+        let line = line.map_err(|_| Err::ResErrorNotOpen)?;
+
+        for word in line.split_whitespace().filter(|word| !word.starts_with(&args.label)) {
+            if !words.insert(word.to_owned()) {
+                continue;
+            }
+            if let Some(vector) = model.word_to_vector(word) {
+                if vector.len() != args.dim as usize {
+                    return Err(Err::ResErrorWrongDimension);
+                }
+                vectors.push((word.to_owned(), vector));
+            }
+        }
+    }
+
+    let (staged, file) = StagedFile::create("vec").map_err(|_| Err::ResErrorNotOpen)?;
+    let mut writer = vec::Writer::new(BufWriter::new(file), vectors.len(), args.dim as usize).map_err(|_| Err::ResErrorNotOpen)?;
+    for &(ref word, ref vector) in &vectors {
+        writer.write(word, vector).map_err(|_| Err::ResErrorNotOpen)?;
+    }
+    writer.finish().map_err(|_| Err::ResErrorNotOpen)?;
+
+    Ok(staged)
+}
+
+/// Joins the tokens of `text` with spaces, as line breaks end an example for fastText.
+/// Tokens starting with the label prefix are dropped, fastText would take them for labels.
+fn escape_text(prefix: &str, text: &str) -> String {
+    let tokens: Vec<&str> = text.split_whitespace()
+        .filter(|token| prefix.is_empty() || !token.starts_with(prefix))
+        .collect();
+
+    tokens.join(" ")
+}
+
+/// Formats an example as `__label__a __label__b text`, adding the prefix to labels without it.
+/// Whitespace in labels is replaced with `_` to keep them one token.
+fn supervised_line(prefix: &str, labels: &[String], text: &str) -> String {
+    let mut line = String::new();
+
+    for label in labels {
+        if !label.starts_with(prefix) {
+            line.push_str(prefix);
+        }
+        line.extend(label.chars().map(|c| if c.is_whitespace() { '_' } else { c }));
+        line.push(' ');
+    }
+    line.push_str(&escape_text(prefix, text));
+
+    line
+}
+
+impl FastText {
+    /// Trains a model on `args.input`.
+    ///
+    /// Pretrained vectors must have `args.dim` dimensions, or `Err::ResErrorWrongDimension` is returned.
+    pub fn train(args: &TrainArgs) -> Result<FastText, Err> {
+        let mut model = FastTextWrapper::default();
+
+        match args.pretrained_vectors {
+            Some(PretrainedVectors::File(ref path)) => {
+                check_vectors_file(path, args.dim)?;
+                model.train(args)?;
+            }
+            Some(PretrainedVectors::Model(ref vectors)) => {
+                let staged = stage_vectors(&**vectors, args)?;
+                model.train(&TrainArgs {
+                    pretrained_vectors: Some(PretrainedVectors::File(staged.0.clone())),
+                    ..args.clone()
+                })?;
+            }
+            None => {
+                model.train(args)?;
+            }
+        }
+
+        Ok(FastText(model))
+    }
+
+    /// Trains a supervised model on `(labels, text)` examples, staged in a temporary file
+    /// which replaces `args.input`.
+    pub fn train_supervised<I>(examples: I, args: &TrainArgs) -> Result<FastText, Err>
+        where I: IntoIterator<Item = (Vec<String>, String)>
+    {
+        let lines = examples.into_iter().map(|(labels, text)| supervised_line(&args.label, &labels, &text));
+        FastText::train_staged(lines, args)
+    }
+
+    /// Trains an unsupervised model on sentences, staged in a temporary file which replaces `args.input`.
+    pub fn train_unsupervised<I>(sentences: I, args: &TrainArgs) -> Result<FastText, Err>
+        where I: IntoIterator, I::Item: AsRef<str>
+    {
+        let lines = sentences.into_iter().map(|sentence| escape_text(&args.label, sentence.as_ref()));
+        FastText::train_staged(lines, args)
+    }
+
+    /// Trains a new supervised model on `(labels, text)` examples, starting from the word, subword
+    /// and label vectors of this model, which stays usable. The examples are staged as by `train_supervised`.
+    ///
+    /// The dimension, loss, n-gram and bucket arguments of this model replace those of `args`
+    /// so that its vectors can be reused. Quantized models can't be fine-tuned.
+    pub fn fine_tune<I>(&self, examples: I, args: &TrainArgs) -> Result<FastText, Err>
+        where I: IntoIterator<Item = (Vec<String>, String)>
+    {
+        let info = self.info()?;
+
+        let lines = examples.into_iter().map(|(labels, text)| supervised_line(&args.label, &labels, &text));
+        let staged = StagedFile::write(lines).map_err(|_| Err::ResErrorNotOpen)?;
+
+        let args = TrainArgs {
+            input: staged.0.clone(),
+            model: ModelKind::Supervised,
+            loss: info.args.loss,
+            dim: info.args.dim,
+            word_ngrams: info.args.word_ngrams,
+            bucket: info.args.bucket,
+            minn: info.args.minn,
+            maxn: info.args.maxn,
+            ..args.clone()
+        };
+
+        let mut model = FastTextWrapper::default();
+        model.fine_tune(&self.0, &args)?;
+
+        Ok(FastText(model))
+    }
+
+    fn train_staged<I: IntoIterator<Item = String>>(lines: I, args: &TrainArgs) -> Result<FastText, Err> {
+        let staged = StagedFile::write(lines).map_err(|_| Err::ResErrorNotOpen)?;
+
+        FastText::train(&TrainArgs {
+            input: staged.0.clone(),
+            ..args.clone()
+        })
+    }
+
+    /// Quantizes a supervised model in place, as saved to a `.ftz` file.
+    pub fn quantize(&mut self, args: &QuantizeArgs) -> Result<(), Err> {
+        self.0.quantize(args).map(|_| ())
+    }
+
+    /// Saves the model in the fastText binary format.
+    pub fn save_model(&self, path: &Path) -> Result<(), Err> {
+        self.0.save_model(path).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_supervised_line() {
+        let labels = vec![String::from("__label__a"), String::from("b c")];
+
+        assert_eq!(supervised_line("__label__", &labels, "one\ntwo\r\nthree"), "__label__a __label__b_c one two three");
+        assert_eq!(supervised_line("#", &[], "text"), "text", "check no labels");
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(escape_text("__label__", "one __label__b two\tthree"), "one two three", "check labels in text are dropped");
+        assert_eq!(escape_text("__label__", "a_label__ __label__"), "a_label__", "check only the prefix is matched");
+        assert_eq!(escape_text("", "one two"), "one two", "check an empty prefix");
+        assert_eq!(supervised_line("#", &[String::from("a")], "#b c"), "#a c");
+    }
+}
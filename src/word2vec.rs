@@ -0,0 +1,267 @@
+//! Word2vec embeddings: export of a loaded model and import as a `WordVectorModel`.
+//!
+//! The binary format is a `rows dim` text header followed by `word ` and `dim` little-endian
+//! floats for each word; the text format is the one of `.vec` files.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use wordvector_base::WordVectorModel;
+
+use format::binary::read_bytes;
+use format::vec::{Error, Reader, Writer};
+use format::DenseMatrix;
+use FastText;
+
+const MAX_WORD_LEN: usize = 4096;
+
+fn read_word<R: BufRead>(reader: &mut R, row: usize) -> Result<String, Error> {
+    let mut word = Vec::new();
+    let mut byte = [0u8; 1];
+
+    loop {
+        match reader.read(&mut byte)? {
+            0 => return Err(Error::Parse { line: row + 2, msg: String::from("unexpected end of file") }),
+            _ => match byte[0] {
+                b' ' if !word.is_empty() => break,
+                b' ' | b'\n' | b'\r' | b'\t' => continue,
+                c => word.push(c),
+            },
+        }
+
+        if word.len() > MAX_WORD_LEN {
+            return Err(Error::Parse { line: row + 2, msg: String::from("word is too long") });
+        }
+    }
+
+    String::from_utf8(word)
+        .map_err(|_| Error::Parse { line: row + 2, msg: String::from("word is not UTF-8") })
+}
+
+fn write_binary_row<W: Write>(writer: &mut W, word: &str, vector: &[f32]) -> io::Result<()> {
+    writer.write_all(word.as_bytes())?;
+    writer.write_all(b" ")?;
+    for v in vector {
+        writer.write_all(&v.to_le_bytes())?;
+    }
+
+    writer.write_all(b"\n")
+}
+
+/// Word vectors imported from word2vec files.
+#[derive(Debug, Clone)]
+pub struct Word2Vec {
+    words: Vec<String>,
+    index: HashMap<String, usize>,
+    vectors: DenseMatrix,
+}
+
+impl Word2Vec {
+    fn new(words: Vec<String>, vectors: DenseMatrix) -> Word2Vec {
+        let index = words.iter()
+            .enumerate()
+            .map(|(i, word)| (word.clone(), i))
+            .collect();
+
+        Word2Vec {
+            words,
+            index,
+            vectors,
+        }
+    }
+
+    pub fn open_binary(path: &Path) -> Result<Word2Vec, Error> {
+        Word2Vec::from_binary(BufReader::new(File::open(path)?))
+    }
+
+    pub fn open_text(path: &Path) -> Result<Word2Vec, Error> {
+        Word2Vec::from_text(BufReader::new(File::open(path)?))
+    }
+
+    pub fn from_binary<R: BufRead>(mut reader: R) -> Result<Word2Vec, Error> {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+
+        let shape: Vec<usize> = header.split_whitespace()
+            .map(|field| field.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| Error::Header { line: 1, msg: format!("wrong header {:?}", header.trim_end()) })?;
+        if shape.len() != 2 {
+            return Err(Error::Header { line: 1, msg: format!("expected `rows dim`, got {:?}", header.trim_end()) });
+        }
+        let (rows, dim) = (shape[0], shape[1]);
+        let row_len = match usize::checked_mul(rows, dim).and_then(|_| dim.checked_mul(4)) {
+            Some(row_len) => row_len,
+            None => return Err(Error::Header { line: 1, msg: format!("too many values for {} rows of dimension {}", rows, dim) }),
+        };
+
+        // the header is not trusted for the allocations: rows are counted as they are read and
+        // the floats of a row read chunk by chunk, so a huge `dim` fails at the end of the file
+        let mut words = Vec::new();
+        let mut data = Vec::new();
+
+        for row in 0..rows {
+            let word = read_word(&mut reader, row)?;
+
+            let buf = read_bytes(&mut reader, row_len).map_err(|err| match err.kind() {
+                io::ErrorKind::UnexpectedEof => Error::RowCount { expected: rows, got: row },
+                _ => Error::Io(err),
+            })?;
+            data.extend(buf.chunks(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])));
+
+            words.push(word);
+        }
+
+        let vectors = DenseMatrix {
+            rows,
+            cols: dim,
+            data,
+        };
+
+        Ok(Word2Vec::new(words, vectors))
+    }
+
+    pub fn from_text<R: BufRead>(reader: R) -> Result<Word2Vec, Error> {
+        let (words, vectors) = Reader::new(reader)?.read_all()?;
+
+        Ok(Word2Vec::new(words, vectors))
+    }
+
+    pub fn write_binary<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        writeln!(writer, "{} {}", self.words.len(), self.vectors.cols)?;
+
+        for (i, word) in self.words.iter().enumerate() {
+            write_binary_row(&mut writer, word, self.vectors.row(i))?;
+        }
+
+        Ok(writer.flush()?)
+    }
+
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    pub fn dim(&self) -> usize {
+        self.vectors.cols
+    }
+}
+
+impl WordVectorModel for Word2Vec {
+    fn word_index(&self, word: &str) -> Option<i64> {
+        self.index.get(word).map(|&i| i as i64)
+    }
+
+    fn word_to_vector(&self, word: &str) -> Option<Vec<f32>> {
+        self.index.get(word).map(|&i| self.vectors.row(i).to_owned())
+    }
+
+    /// The average of the vectors of known words.
+    fn sentence_to_vector(&self, text: &str) -> Option<Vec<f32>> {
+        let known: Vec<usize> = text.split_whitespace().filter_map(|word| self.index.get(word).cloned()).collect();
+        if known.is_empty() {
+            return None;
+        }
+
+        let mut vector = vec![0f32; self.dim()];
+        for &i in &known {
+            for (v, w) in vector.iter_mut().zip(self.vectors.row(i)) {
+                *v += *w;
+            }
+        }
+
+        for v in vector.iter_mut() {
+            *v /= known.len() as f32;
+        }
+
+        Some(vector)
+    }
+}
+
+impl FastText {
+    pub(crate) fn vocabulary(&self) -> Vec<String> {
+        let dict = self.0.get_dictionary();
+
+        (0..dict.words_count())
+            .filter_map(|i| dict.get_word(i as i64))
+            .collect()
+    }
+
+    /// The words of the vocabulary with their vectors, so that the header counts only written rows.
+    fn vocabulary_vectors(&self) -> Vec<(String, Vec<f32>)> {
+        self.vocabulary()
+            .into_iter()
+            .filter_map(|word| self.0.word_to_vector(&word).map(|vector| (word, vector.as_slice().to_vec())))
+            .collect()
+    }
+
+    /// Writes the vectors of the vocabulary in the word2vec binary format.
+    pub fn save_word2vec_binary<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let rows = self.vocabulary_vectors();
+
+        writeln!(writer, "{} {}", rows.len(), self.dimension())?;
+        for &(ref word, ref vector) in &rows {
+            write_binary_row(&mut writer, word, vector)?;
+        }
+
+        Ok(writer.flush()?)
+    }
+
+    /// Writes the vectors of the vocabulary in the word2vec text format.
+    pub fn save_word2vec_text<W: Write>(&self, writer: W) -> Result<(), Error> {
+        let rows = self.vocabulary_vectors();
+
+        let mut writer = Writer::new(writer, rows.len(), self.dimension())?;
+        for &(ref word, ref vector) in &rows {
+            writer.write(word, vector)?;
+        }
+
+        writer.finish().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_word2vec_binary() {
+        let model = Word2Vec::from_text("2 3\nкот 1 -0.5 0.25\nдуб 0.125 3 -2\n".as_bytes()).unwrap();
+
+        let mut bytes = Vec::new();
+        model.write_binary(&mut bytes).unwrap();
+
+        let loaded = Word2Vec::from_binary(bytes.as_slice()).unwrap();
+        assert_eq!(loaded.words(), model.words(), "check words");
+        assert_eq!(loaded.word_to_vector("дуб"), Some(vec![0.125, 3.0, -2.0]), "check vectors");
+        assert_eq!(loaded.word_index("кот"), Some(0), "check index");
+        assert_eq!(loaded.sentence_to_vector("кот дуб"), Some(vec![0.5625, 1.25, -0.875]), "check sentence");
+        assert_eq!(loaded.sentence_to_vector("цепь"), None, "check unknown sentence");
+
+        match Word2Vec::from_binary(&bytes[..bytes.len() - 8]) {
+            Err(Error::RowCount { expected: 2, got: 1 }) => assert!(true),
+            res => assert!(false, "failed to raise a row count error, got {:?}", res.map(|_| ())),
+        }
+
+        match Word2Vec::from_binary(format!("{} 3\n", usize::max_value() / 2).as_bytes()) {
+            Err(Error::Header { line: 1, .. }) => assert!(true),
+            res => assert!(false, "failed to raise an overflow error, got {:?}", res.map(|_| ())),
+        }
+
+        match Word2Vec::from_binary(format!("{} 3\n", u32::max_value()).as_bytes()) {
+            Err(Error::Parse { line: 2, .. }) => assert!(true),
+            res => assert!(false, "failed to raise an end of file error for a huge header, got {:?}", res.map(|_| ())),
+        }
+
+        let mut huge_dim = "1 1099511627776\nкот ".as_bytes().to_vec();
+        huge_dim.extend_from_slice(&1f32.to_le_bytes());
+        match Word2Vec::from_binary(huge_dim.as_slice()) {
+            Err(Error::RowCount { expected: 1, got: 0 }) => assert!(true),
+            res => assert!(false, "failed to raise a row count error for a huge dimension, got {:?}", res.map(|_| ())),
+        }
+
+        let empty = Word2Vec::from_binary("0 1099511627776\n".as_bytes()).unwrap();
+        assert_eq!(empty.sentence_to_vector("кот"), None, "check sentence of a huge dimension");
+    }
+}
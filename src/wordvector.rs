@@ -1,3 +1,4 @@
+use std::io::Read;
 use std::path::Path;
 
 use wordvector_base::WordVectorModel;
@@ -18,6 +19,49 @@ impl FastText {
 
         Ok(FastText(model))
     }
+
+    /// Loads a `.bin` model by memory-mapping the file read-only.
+    ///
+    /// The input and output matrices are served straight from the mapping, so processes loading
+    /// the same file share the page cache; a matrix not aligned on 4 bytes in the file is copied.
+    /// Quantized (`.ftz`) models are not supported; the `.vec` file is not loaded.
+    pub fn with_mapped_model(path: &Path) -> Result<FastText, Err> {
+        let mut model = FastTextWrapper::default();
+        model.load_model_mapped(path)?;
+
+        Ok(FastText(model))
+    }
+
+    /// Loads a model in the fastText binary format (`.bin`/`.ftz`) from any `Read` source.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<FastText, Err> {
+        let mut model = FastTextWrapper::default();
+        model.load_model_from_reader(&mut reader)?;
+
+        Ok(FastText(model))
+    }
+
+    /// Loads a model from an in-memory image of a `.bin`/`.ftz` file, e.g. one embedded with `include_bytes!`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<FastText, Err> {
+        FastText::from_reader(bytes)
+    }
+
+    /// The dimension of the word and sentence vectors.
+    pub fn dimension(&self) -> usize {
+        self.0.dimension()
+    }
+
+    /// Writes the vector of `word` into `out`, which must be of the model dimension.
+    ///
+    /// Unlike `word_to_vector` it doesn't allocate a vector per call.
+    pub fn word_vector_into(&self, word: &str, out: &mut [f32]) -> Result<(), Err> {
+        self.0.word_vector_into(word, out).map(|_| ())
+    }
+
+    /// The word itself, if known, and its character n-grams with their input vectors,
+    /// as `fasttext print-ngrams` prints them.
+    pub fn ngram_vectors(&self, word: &str) -> Vec<(String, Vec<f32>)> {
+        self.0.ngram_vectors(word)
+    }
 }
 
 impl WordVectorModel for FastText {
@@ -33,20 +77,22 @@ impl WordVectorModel for FastText {
     }
 
     fn sentence_to_vector(&self, text: &str) -> Option<Vec<f32>> {
-        match self.0.word_to_vector(text) {
-            Some(vector) => Some(vector.as_slice().to_owned()),
-            None => None,
-        }
+        self.sentence_vector(text)
     }
 }
 
 #[cfg(test)]
 mod testing {
     use super::*;
+    use testing::fixtures::{missing, stem, UNSUPERVISED};
 
     #[test]
     fn test_fasttextmodel_new() {
-        match FastText::with_model(Path::new("./test-data/unsupervised_model")) {
+    if missing() {
+        return;
+    }
+
+        match FastText::with_model(&stem(UNSUPERVISED)) {
             Ok(model) => assert!(model.word_index("златом").unwrap_or_default() > 0, "check model working"),
             Err(err) => assert!(false, "failed to create a fasttext model {:?}", err),
         };